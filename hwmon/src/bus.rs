@@ -3,7 +3,7 @@
 
 use std::ffi::OsStr;
 use std::fmt;
-use std::fs;
+use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
@@ -12,7 +12,7 @@ use crate::error::*;
 use crate::sysfs::*;
 
 #[allow(non_snake_case)]
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum BusType {
     I2C,
     ISA,
@@ -41,15 +41,52 @@ impl fmt::Display for BusType {
     }
 }
 
-#[derive(Clone)]
+impl BusType {
+    /// Return the bus type of a device from the name of its sysfs subsystem, like
+    /// `i2c` or `pci`. Platform devices are on the ISA bus.
+    ///
+    /// Return `None` for subsystems hwmon chips are not known to be attached to.
+    pub fn from_subsystem(subsystem: &str) -> Option<BusType> {
+        match subsystem {
+            "i2c" => Some(BusType::I2C),
+            "spi" => Some(BusType::SPI),
+            "pci" => Some(BusType::PCI),
+            "scsi" => Some(BusType::SCSI),
+            "platform" | "of_platform" => Some(BusType::ISA),
+            "acpi" => Some(BusType::ACPI),
+            "hid" => Some(BusType::HID),
+            "mdio_bus" => Some(BusType::MDIO),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct Bus {
     bus_type: BusType,
-    bus_number: i16,
+    bus_number: i32,
     context: Context,
 }
 
+/// Two buses are equal when they have the same type and number, whatever the
+/// context they were read from.
+impl PartialEq for Bus {
+    fn eq(&self, other: &Bus) -> bool {
+        self.bus_type == other.bus_type && self.bus_number == other.bus_number
+    }
+}
+
+impl Eq for Bus {}
+
+impl Hash for Bus {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.bus_type.hash(state);
+        self.bus_number.hash(state);
+    }
+}
+
 impl Bus {
-    pub fn new(bus_type: BusType, bus_number: i16, context: Context) -> Bus {
+    pub fn new(bus_type: BusType, bus_number: i32, context: Context) -> Bus {
         Bus {
             bus_type,
             bus_number,
@@ -63,12 +100,48 @@ impl Bus {
     }
 
     /// Return the bus number
-    pub fn number(&self) -> i16 {
+    pub fn number(&self) -> i32 {
         self.bus_number
     }
 
+    pub(crate) fn context(&self) -> &Context {
+        &self.context
+    }
+
+    /// Return the sysfs directory of the bus adapter, like `/sys/class/i2c-adapter/i2c-3`.
+    ///
+    /// Only I2C buses have one directory per adapter, `None` is returned for the other
+    /// bus types.
+    pub fn sysfs_path(&self) -> Option<PathBuf> {
+        match self.bus_type {
+            BusType::I2C => Some(
+                self.context
+                    .sysfs_root()
+                    .join(format!("class/i2c-adapter/i2c-{}", self.bus_number)),
+            ),
+            _ => None,
+        }
+    }
+
     /// Return the adapter name of the bus. If it could not be found, it returns `None`
-    pub fn adapter_name(&self) -> Option<&str> {
+    ///
+    /// The name given by a `bus` statement of the configuration, like
+    /// `bus "i2c-0" "SMBus I801"`, takes precedence over the sysfs one. The name is
+    /// returned owned, since `Context::reload_config` can replace the configuration
+    /// while the bus is borrowed.
+    pub fn adapter_name(&self) -> Option<String> {
+        #[cfg(feature = "sensorsconf")]
+        if self.bus_type == BusType::I2C {
+            let number = format!("i2c-{}", self.bus_number);
+            if let Some(name) = self.context.config().bus_adapter(&number) {
+                return Some(name.to_owned());
+            }
+        }
+
+        self.sysfs_adapter_name().map(String::from)
+    }
+
+    fn sysfs_adapter_name(&self) -> Option<&str> {
         match self.bus_type {
             BusType::ISA => Some("ISA adapter"),
             BusType::PCI => Some("PCI adapter"),
@@ -101,11 +174,11 @@ impl Bus {
 pub(crate) struct BusAdapter {
     name: String,
     bus_type: BusType,
-    bus_number: i16,
+    bus_number: i32,
 }
 
 impl BusAdapter {
-    fn from_sysfs_i2c(path: &Path) -> Result<Option<BusAdapter>, Error> {
+    fn from_sysfs_i2c(fs: &dyn FsProvider, path: &Path) -> Result<Option<BusAdapter>, Error> {
         let classdev = path.file_name().and_then(OsStr::to_str).unwrap();
 
         let prefix = "i2c-";
@@ -114,7 +187,7 @@ impl BusAdapter {
         }
         let (_, digits) = classdev.split_at(prefix.len());
 
-        let bus_number = i16::from_str(digits)?;
+        let bus_number = i32::from_str(digits)?;
 
         if bus_number == 9191 {
             return Ok(None); // legacy ISA
@@ -123,8 +196,9 @@ impl BusAdapter {
         // Get the adapter name from the classdev "name" attribute
         // (Linux 2.6.20 and later). If it fails, fall back to
         // the device "name" attribute (for older kernels).
-        let name =
-            sysfs_read_attr(path, "name").or_else(|_| sysfs_read_attr(path, "device/name"))?;
+        let name = fs
+            .read_attr(path, "name")
+            .or_else(|_| fs.read_attr(path, "device/name"))?;
 
         Ok(Some(BusAdapter {
             name,
@@ -141,35 +215,34 @@ impl BusAdapter {
         self.bus_type
     }
 
-    pub fn bus_number(&self) -> i16 {
+    pub fn bus_number(&self) -> i32 {
         self.bus_number
     }
 }
 
-pub(crate) fn read_sysfs_busses() -> Result<Vec<BusAdapter>, Error> {
+pub(crate) fn read_sysfs_busses(
+    fs: &dyn FsProvider,
+    sysfs_root: &Path,
+) -> Result<Vec<BusAdapter>, Error> {
+    sysfs_check_root(fs, sysfs_root)?;
+
     let mut res = Vec::new();
 
-    let mut adapter_path = PathBuf::from(SYSFS_MOUNT);
+    let mut adapter_path = sysfs_root.to_owned();
     adapter_path.push("class/i2c-adapter");
 
-    if adapter_path.is_dir() {
-        for entry in fs::read_dir(adapter_path)? {
-            let entry = entry?;
-            let path = entry.path();
-
-            if let Some(bus) = BusAdapter::from_sysfs_i2c(path.as_ref())? {
+    if fs.is_dir(&adapter_path) {
+        for path in fs.read_dir(&adapter_path)? {
+            if let Some(bus) = BusAdapter::from_sysfs_i2c(fs, path.as_ref())? {
                 res.push(bus);
             }
         }
     } else {
-        let mut i2c_path = PathBuf::from(SYSFS_MOUNT);
+        let mut i2c_path = sysfs_root.to_owned();
         i2c_path.push("bus/i2c/devices");
 
-        for entry in fs::read_dir(i2c_path)? {
-            let entry = entry?;
-            let path = entry.path();
-
-            if let Some(bus) = BusAdapter::from_sysfs_i2c(path.as_ref())? {
+        for path in fs.read_dir(&i2c_path)? {
+            if let Some(bus) = BusAdapter::from_sysfs_i2c(fs, path.as_ref())? {
                 res.push(bus);
             }
         }
@@ -180,20 +253,102 @@ pub(crate) fn read_sysfs_busses() -> Result<Vec<BusAdapter>, Error> {
 
 #[cfg(test)]
 mod tests {
+    #[test]
+    // The context has interior mutability, but it is not part of the hash.
+    #[allow(clippy::mutable_key_type)]
+    fn bus_hash_ignores_context() {
+        use super::{Bus, BusType};
+        use crate::context::Context;
+        use std::collections::HashSet;
+
+        let mut buses = HashSet::new();
+        buses.insert(Bus::new(BusType::I2C, 1, Context::empty()));
+        buses.insert(Bus::new(BusType::I2C, 1, Context::empty()));
+        buses.insert(Bus::new(BusType::I2C, 2, Context::empty()));
+        buses.insert(Bus::new(BusType::ISA, 1, Context::empty()));
+        assert_eq!(buses.len(), 3);
+    }
+
+    #[test]
+    fn bus_type_from_subsystem() {
+        use super::BusType;
+
+        assert_eq!(BusType::from_subsystem("i2c"), Some(BusType::I2C));
+        assert_eq!(BusType::from_subsystem("spi"), Some(BusType::SPI));
+        assert_eq!(BusType::from_subsystem("pci"), Some(BusType::PCI));
+        assert_eq!(BusType::from_subsystem("scsi"), Some(BusType::SCSI));
+        assert_eq!(BusType::from_subsystem("platform"), Some(BusType::ISA));
+        assert_eq!(BusType::from_subsystem("of_platform"), Some(BusType::ISA));
+        assert_eq!(BusType::from_subsystem("acpi"), Some(BusType::ACPI));
+        assert_eq!(BusType::from_subsystem("hid"), Some(BusType::HID));
+        assert_eq!(BusType::from_subsystem("mdio_bus"), Some(BusType::MDIO));
+        assert_eq!(BusType::from_subsystem("usb"), None);
+        assert_eq!(BusType::from_subsystem(""), None);
+    }
+
+    #[test]
+    fn bus_sysfs_path() {
+        use super::{Bus, BusType};
+        use crate::context::Context;
+
+        let bus = Bus::new(BusType::I2C, 3, Context::empty());
+        assert_eq!(
+            bus.sysfs_path(),
+            Some(std::path::PathBuf::from("/sys/class/i2c-adapter/i2c-3"))
+        );
+
+        let bus = Bus::new(BusType::PCI, 0, Context::empty());
+        assert_eq!(bus.sysfs_path(), None);
+    }
+
+    #[test]
+    fn bus_adapter_from_sysfs_i2c_large_number() {
+        use super::BusAdapter;
+        use crate::sysfs::StdFs;
+        use crate::testutil::TempDir;
+
+        let dir = TempDir::new();
+        dir.file("i2c-40000/name", "SMBus I801 adapter");
+        let adapter = BusAdapter::from_sysfs_i2c(&StdFs, &dir.path().join("i2c-40000"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(adapter.bus_number(), 40000);
+        assert_eq!(adapter.name(), "SMBus I801 adapter");
+
+        dir.file("i2c-99999999999/name", "SMBus I801 adapter");
+        assert!(matches!(
+            BusAdapter::from_sysfs_i2c(&StdFs, &dir.path().join("i2c-99999999999")),
+            Err(crate::Error::ParseInt(_))
+        ));
+    }
+
     #[test]
     fn bus_adapter_from_sysfs_i2c_legacy_isa() {
         use super::BusAdapter;
+        use crate::sysfs::StdFs;
 
         let path = std::path::PathBuf::from("/sys/class/i2c-adapter/i2c-9191/");
-        assert_eq!(BusAdapter::from_sysfs_i2c(path.as_path()).unwrap(), None);
+        assert_eq!(
+            BusAdapter::from_sysfs_i2c(&StdFs, path.as_path()).unwrap(),
+            None
+        );
 
         let path = std::path::PathBuf::from("/sys/bus/i2c/devices/i2c-9191/");
-        assert_eq!(BusAdapter::from_sysfs_i2c(path.as_path()).unwrap(), None);
+        assert_eq!(
+            BusAdapter::from_sysfs_i2c(&StdFs, path.as_path()).unwrap(),
+            None
+        );
 
         let path = std::path::PathBuf::from("/sys/class/i2c-adapter/i2c-0/");
-        assert_ne!(BusAdapter::from_sysfs_i2c(path.as_path()).unwrap(), None);
+        assert_ne!(
+            BusAdapter::from_sysfs_i2c(&StdFs, path.as_path()).unwrap(),
+            None
+        );
 
         let path = std::path::PathBuf::from("/sys/class/i2c-adapter/i2c-0/");
-        assert_ne!(BusAdapter::from_sysfs_i2c(path.as_path()).unwrap(), None);
+        assert_ne!(
+            BusAdapter::from_sysfs_i2c(&StdFs, path.as_path()).unwrap(),
+            None
+        );
     }
 }
@@ -3,16 +3,25 @@
 
 use std::collections::btree_map;
 use std::ffi::OsStr;
-use std::io::Read;
+use std::io;
 use std::path::{Path, PathBuf};
+#[cfg(feature = "sensorsconf")]
+use std::rc::Rc;
 use std::str::FromStr;
 
 use crate::bus::{Bus, BusType};
 use crate::context::Context;
+#[cfg(feature = "sensorsconf")]
+use crate::context::FeatureConfig;
 use crate::error::*;
-use crate::feature::{Feature, FeatureType};
-use crate::subfeature::Subfeature;
+use crate::feature::{Feature, FeatureReadings, FeatureType};
+use crate::owned::OwnedChip;
+use crate::subfeature::{Subfeature, SubfeatureType, Temperature};
 use crate::sysfs::*;
+use crate::unit::Unit;
+
+/// Values of all the subfeatures of a chip, by feature type and number.
+pub type ChipReadings = btree_map::BTreeMap<(FeatureType, u32), FeatureReadings>;
 
 #[derive(Debug)]
 pub struct FeatureIter<'a> {
@@ -25,27 +34,112 @@ impl<'a> Iterator for FeatureIter<'a> {
     fn next(&mut self) -> Option<Self::Item> {
         self.inner.next()
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for FeatureIter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
 }
 
+impl ExactSizeIterator for FeatureIter<'_> {}
+
+#[derive(Debug)]
 pub struct Chip {
     path: PathBuf,
+    /// `path` with its symbolic links resolved, to match the paths given to
+    /// `subfeature_by_path`.
+    canonical_path: PathBuf,
     prefix: String,
     bus: Bus,
     address: u32,
     features: btree_map::BTreeMap<(FeatureType, u32), Feature>,
 }
 
+/// Two chips are equal when they have the same `stable_id`, whatever their sysfs path.
+impl PartialEq for Chip {
+    fn eq(&self, other: &Chip) -> bool {
+        self.prefix == other.prefix && self.bus == other.bus && self.address == other.address
+    }
+}
+
+impl Eq for Chip {}
+
 impl Chip {
     /// Chip prefix
     pub fn prefix(&self) -> &str {
         self.prefix.as_ref()
     }
 
+    /// Return `true` if the chip is not attached to a hardware bus, like the `acpitz`
+    /// thermal zones or the `thinkpad` platform driver.
+    pub fn is_virtual(&self) -> bool {
+        self.bus.get_type() == BusType::Virtual
+    }
+
     /// The chip address on the bus.
     pub fn address(&self) -> u32 {
         self.address
     }
 
+    /// The chip address formatted the way its bus names devices, e.g. `0000:00:18.3`
+    /// for PCI or `0x2a` for I2C.
+    ///
+    /// PCI and SCSI addresses are unpacked back to their components, SCSI addresses
+    /// being prefixed by the host number.
+    pub fn address_string(&self) -> String {
+        let address = self.address;
+        match self.bus.get_type() {
+            BusType::ISA => format!("0x{:04x}", address),
+            BusType::PCI => format!(
+                "{:04x}:{:02x}:{:02x}.{:x}",
+                address >> 16,
+                (address >> 8) & 0xff,
+                (address >> 3) & 0x1f,
+                address & 0x7
+            ),
+            BusType::I2C => format!("0x{:02x}", address),
+            BusType::SPI => format!("{}", address),
+            BusType::SCSI => format!(
+                "{}:{}:{}:{:x}",
+                self.bus.number(),
+                address >> 8,
+                (address >> 4) & 0xf,
+                address & 0xf
+            ),
+            BusType::HID | BusType::ACPI | BusType::MDIO | BusType::Virtual => {
+                format!("0x{:x}", address)
+            }
+        }
+    }
+
+    /// Return an identifier of the physical chip, made of its prefix, bus type, bus
+    /// number and address, like `lm75:I2C:1:48`.
+    ///
+    /// Unlike the sysfs path, whose `hwmonN` index depends on the probe order, the
+    /// identifier is the same across reboots.
+    pub fn stable_id(&self) -> String {
+        format!(
+            "{}:{}:{}:{:x}",
+            self.prefix,
+            self.bus.get_type(),
+            self.bus.number(),
+            self.address
+        )
+    }
+
+    /// Convert the chip into an `OwnedChip`, which holds no reference to the `Context`
+    /// and can be sent to another thread.
+    ///
+    /// The labels are resolved during the conversion.
+    pub fn into_owned(self) -> OwnedChip {
+        OwnedChip::new(&self)
+    }
+
     /// Return the sysfs directory path of the chip.
     pub fn path(&self) -> &Path {
         self.path.as_ref()
@@ -55,13 +149,55 @@ impl Chip {
         &self.bus
     }
 
+    /// Parse a chip name formatted like `name`, such as `coretemp-isa-0000` or
+    /// `lm75-i2c-1-48`, into its prefix, bus type, bus number and address.
+    ///
+    /// This allows matching a requested chip without scanning sysfs. The bus number is
+    /// `0` for the bus types without one. Return `None` if the name is not valid.
+    pub fn parse_name(name: &str) -> Option<(String, BusType, i32, u32)> {
+        let parts: Vec<&str> = name.split('-').collect();
+
+        let numbered = |bus: &str| match bus {
+            "i2c" => Some(BusType::I2C),
+            "spi" => Some(BusType::SPI),
+            "hid" => Some(BusType::HID),
+            "scsi" => Some(BusType::SCSI),
+            _ => None,
+        };
+        let unnumbered = |bus: &str| match bus {
+            "isa" => Some(BusType::ISA),
+            "pci" => Some(BusType::PCI),
+            "acpi" => Some(BusType::ACPI),
+            "mdio" => Some(BusType::MDIO),
+            "virtual" => Some(BusType::Virtual),
+            _ => None,
+        };
+
+        let (prefix_len, bus_type, bus_number) = match parts.as_slice() {
+            [.., bus, number, _] if numbered(bus).is_some() && parts.len() >= 4 => {
+                (parts.len() - 3, numbered(bus)?, i32::from_str(number).ok()?)
+            }
+            [.., bus, _] if unnumbered(bus).is_some() && parts.len() >= 3 => {
+                (parts.len() - 2, unnumbered(bus)?, 0)
+            }
+            _ => return None,
+        };
+
+        let prefix = parts[..prefix_len].join("-");
+        let address = u32::from_str_radix(parts.last()?, 16).ok()?;
+        if prefix.is_empty() {
+            return None;
+        }
+        Some((prefix, bus_type, bus_number, address))
+    }
+
     /// Chip name from its internal representation.
     pub fn name(&self) -> String {
         match self.bus().get_type() {
             BusType::ISA => format!("{}-isa-{:04x}", self.prefix(), self.address()),
             BusType::PCI => format!("{}-pci-{:04x}", self.prefix(), self.address()),
             BusType::I2C => format!(
-                "{}-i2C-{}-{:02x}",
+                "{}-i2c-{}-{:02x}",
                 self.prefix(),
                 self.bus.number(),
                 self.address()
@@ -95,33 +231,268 @@ impl Chip {
         self.features.get(&(ftype, number))
     }
 
-    /// An iterator visiting all features in arbitrary order.
-    pub fn features_iter(&self) -> FeatureIter {
+    /// Return the feature of the given type as mutable, like to set the compute
+    /// statement of one of its subfeatures with `Feature::set_compute_statement`.
+    pub fn feature_mut(&mut self, ftype: FeatureType, number: u32) -> Option<&mut Feature> {
+        self.features.get_mut(&(ftype, number))
+    }
+
+    /// Return the number of features of the chip.
+    pub fn feature_count(&self) -> usize {
+        self.features.len()
+    }
+
+    /// Return the number of subfeatures of all the features of the chip.
+    pub fn subfeature_count(&self) -> usize {
+        self.features_iter()
+            .map(|feature| feature.subfeatures_iter().len())
+            .sum()
+    }
+
+    /// An iterator visiting all features ordered by type, then by number.
+    pub fn features_iter(&self) -> FeatureIter<'_> {
         FeatureIter {
             inner: self.features.values(),
         }
     }
 
+    /// An iterator visiting the features of the given type, by increasing number.
+    pub fn features_of_type(&self, ftype: FeatureType) -> impl Iterator<Item = &Feature> {
+        self.features
+            .range((ftype, 0)..=(ftype, u32::MAX))
+            .map(|(_, feature)| feature)
+    }
+
+    /// Return the numbers of the features of the given type, in increasing order.
+    ///
+    /// Feature numbers may have gaps, like `temp1` and `temp3` without `temp2`.
+    pub fn feature_numbers(&self, ftype: FeatureType) -> Vec<u32> {
+        self.features
+            .keys()
+            .filter(|(feature_type, _)| *feature_type == ftype)
+            .map(|(_, number)| *number)
+            .collect()
+    }
+
+    /// Read the main value of every feature, see `Feature::input_value`, as
+    /// `(label, value, unit)` in feature order. Features without a readable main
+    /// value are skipped.
+    pub fn inputs(&self) -> Vec<(String, f64, Unit)> {
+        self.features_iter()
+            .filter_map(|feature| {
+                let unit = feature.input_subfeature()?.get_type().unit();
+                let value = feature.input_value()?;
+                Some((feature.label(), value, unit))
+            })
+            .collect()
+    }
+
+    /// Return the highest temperature measured by the chip, in °C.
+    ///
+    /// Sensors which can not be read, or whose `fault` flag is set, are ignored.
+    /// Return `None` if no temperature could be read.
+    pub fn max_temperature(&self) -> Option<f64> {
+        self.features_iter()
+            .filter(|feature| feature.get_type() == FeatureType::Temperature)
+            .filter(|feature| {
+                feature
+                    .subfeature(SubfeatureType::Temperature(Temperature::Fault))
+                    .and_then(|fault| fault.read_value().ok())
+                    .map_or(true, |fault| fault == 0.0)
+            })
+            .filter_map(|feature| {
+                feature
+                    .subfeature(SubfeatureType::Temperature(Temperature::Input))?
+                    .read_value()
+                    .ok()
+            })
+            .reduce(f64::max)
+    }
+
+    /// Return `true` if an alarm of any feature of the chip is raised.
+    pub fn any_alarm_active(&self) -> bool {
+        self.features_iter()
+            .any(|feature| !feature.active_alarms().is_empty())
+    }
+
+    /// Return the subfeature backed by the given sysfs file, if any.
+    ///
+    /// The symbolic links of the directory of the file are resolved, so a path through
+    /// the device directory, like `/sys/class/hwmon/hwmon0/device/hwmon/hwmon0/temp1_crit`,
+    /// matches as well as `/sys/class/hwmon/hwmon0/temp1_crit`. Return `None` if the
+    /// directory can not be resolved.
+    pub fn subfeature_by_path(&self, path: &Path) -> Option<&Subfeature> {
+        let name = path.file_name()?;
+        let dir = match path.parent() {
+            Some(dir) if dir != Path::new("") => dir,
+            _ => Path::new("."),
+        };
+        if self.bus.context().fs().canonicalize(dir).ok()? != self.canonical_path {
+            return None;
+        }
+
+        self.features_iter()
+            .flat_map(Feature::subfeatures_iter)
+            .find(|subfeature| OsStr::new(subfeature.name()) == name)
+    }
+
+    /// Enable or disable all the audible alarms of the chip, through its `beep_enable`
+    /// attribute.
+    ///
+    /// Return `Error::SubfeatureType` if the chip has no `beep_enable` attribute, and
+    /// `Error::Access` if it is not writable.
+    pub fn set_beep_enable(&self, enabled: bool) -> Result<(), Error> {
+        self.feature(FeatureType::BeepEnable, 0)
+            .and_then(|feature| feature.subfeature(SubfeatureType::BeepEnable))
+            .ok_or(Error::SubfeatureType)?
+            .write_value(if enabled { 1.0 } else { 0.0 })
+    }
+
+    /// An iterator visiting the subfeatures of all the features which can be written.
+    pub fn writable_subfeatures(&self) -> impl Iterator<Item = &Subfeature> {
+        self.features_iter().flat_map(Feature::writable_subfeatures)
+    }
+
+    /// Read the value of every subfeature of the chip in a single pass.
+    ///
+    /// This gives a point-in-time picture of the chip. A subfeature which can not
+    /// be read is recorded with its error instead of aborting the snapshot.
+    pub fn read_snapshot(&self) -> ChipReadings {
+        self.features
+            .iter()
+            .map(|(key, feature)| (*key, feature.read_all_values()))
+            .collect()
+    }
+
+    /// Same as `read_snapshot`, reading the sysfs files without blocking the async
+    /// runtime. The subfeatures are read one after the other.
+    #[cfg(feature = "async")]
+    pub async fn read_snapshot_async(&self) -> ChipReadings {
+        let mut readings = ChipReadings::new();
+        for (key, feature) in self.features.iter() {
+            let mut values = FeatureReadings::new();
+            for subfeature in feature.subfeatures_iter() {
+                values.insert(subfeature.get_type(), subfeature.read_value_async().await);
+            }
+            readings.insert(*key, values);
+        }
+        readings
+    }
+
+    /// Read the `update_interval` attribute of the chip, the time between two
+    /// refreshes of the readings by the hardware, in milliseconds.
+    ///
+    /// Returns `None` if the chip has no such attribute.
+    pub fn update_interval(&self) -> Option<Result<f64, Error>> {
+        let fs = self.bus.context().fs();
+        if !fs.is_file(&self.path.join("update_interval")) {
+            return None;
+        }
+        Some(
+            fs.read_attr(&self.path, "update_interval")
+                .map_err(Error::from)
+                .and_then(|value| Ok(value.parse::<f64>()?)),
+        )
+    }
+
+    /// Write the `update_interval` attribute of the chip, in milliseconds.
+    ///
+    /// The driver may round the value to an interval supported by the hardware.
+    pub fn set_update_interval(&self, interval: f64) -> Result<(), Error> {
+        if !interval.is_finite() || interval < 0.0 {
+            return Err(Error::InvalidValue);
+        }
+        let interval = format!("{}", interval.round() as u64);
+        let fs = self.bus.context().fs();
+        Ok(fs.write_attr(&self.path, "update_interval", &interval)?)
+    }
+
+    /// Return the name of the adapter of the chip bus, like `SMBus I801 adapter at f040`.
+    ///
+    /// The name is resolved on each call, from the `bus` statements of the current
+    /// configuration then from sysfs, so chips built before `Context::reload_config`
+    /// report the new name. See `Bus::adapter_name`.
+    pub fn adapter_name(&self) -> Option<String> {
+        self.bus.adapter_name()
+    }
+
+    /// Read an attribute of the chip which is not modeled as a feature, like
+    /// `pwm1_auto_channels_temp` or `device/vendor`, relative to the chip directory.
+    pub fn read_attr(&self, name: &str) -> io::Result<String> {
+        self.bus.context().fs().read_attr(&self.path, name)
+    }
+
+    /// Return the name of the kernel driver bound to the chip device.
+    ///
+    /// The name is resolved from the `device/driver` symlink, falling back to the
+    /// device `modalias` attribute for the `platform`, `i2c` and `spi` buses, whose
+    /// aliases are driver names. Returns `None` for chips without a device, like
+    /// virtual ones, and for other modaliases, like the pci ones made of device IDs.
+    pub fn driver(&self) -> Option<String> {
+        let fs = self.bus.context().fs();
+        let device = self.path.join("device");
+
+        if let Some(driver) = fs
+            .read_link(&device.join("driver"))
+            .ok()
+            .and_then(|link| link.file_name().and_then(OsStr::to_str).map(String::from))
+        {
+            return Some(driver);
+        }
+
+        // modalias is in the form "<subsystem>:<alias>"
+        let modalias = fs.read_attr(&device, "modalias").ok()?;
+        match modalias.split_once(':') {
+            Some(("platform" | "i2c" | "spi", alias)) => Some(alias.to_owned()),
+            _ => None,
+        }
+    }
+
+    /// Return the `(fan, pwm)` feature number pairs where the pwm output drives the fan.
+    ///
+    /// The hwmon sysfs ABI does not describe this relationship, so the conventional
+    /// index match is used: `pwmN` is assumed to control `fanN`.
+    pub fn fan_pwm_links(&self) -> Vec<(u32, u32)> {
+        self.features
+            .keys()
+            .filter(|(ftype, _)| *ftype == FeatureType::Fan)
+            .filter(|(_, number)| self.features.contains_key(&(FeatureType::Pwm, *number)))
+            .map(|(_, number)| (*number, *number))
+            .collect()
+    }
+
+    #[cfg(test)]
     pub(crate) fn from_path<'a, T: Into<Option<&'a Path>>>(
         hwmon_path: &Path,
         dev_path: T,
         context: &Context,
     ) -> Result<Chip, ChipError> {
-        let dev_path = dev_path.into();
+        Chip::from_path_skipping(hwmon_path, dev_path.into(), context, &mut Vec::new())
+    }
+
+    /// Same as `from_path`, adding the files of the chip directory which look like
+    /// subfeatures but can not be read as one to `skipped`.
+    fn from_path_skipping(
+        hwmon_path: &Path,
+        dev_path: Option<&Path>,
+        context: &Context,
+        skipped: &mut Vec<(PathBuf, Error)>,
+    ) -> Result<Chip, ChipError> {
+        let fs = context.fs();
 
-        let prefix = sysfs_read_attr(hwmon_path, "name")?;
+        let prefix = fs.read_attr(hwmon_path, "name")?;
 
         // Find bus type
         let mut bus = Bus::new(BusType::Virtual, 0, context.clone());
         let mut address = 0u32;
 
         if let Some(dev_path) = dev_path {
-            let dev_link_path = dev_path.read_link()?;
+            let dev_link_path = fs.read_link(dev_path)?;
             let dev_name = dev_link_path.file_name().and_then(OsStr::to_str).unwrap();
 
             let mut link_path = dev_path.to_owned();
             link_path.push("subsystem");
-            let subsys_path = link_path.read_link()?;
+            let subsys_path = fs.read_link(&link_path)?;
             let subsys = subsys_path.file_name().and_then(OsStr::to_str).unwrap();
 
             let (_bus, _address) = get_chip_bus_from_name(subsys, dev_name, context)?;
@@ -132,42 +503,57 @@ impl Chip {
         // read_dynamic_chip
         let mut chip = Chip {
             path: hwmon_path.to_owned(),
+            canonical_path: fs
+                .canonicalize(hwmon_path)
+                .unwrap_or_else(|_| hwmon_path.to_owned()),
             prefix,
             bus,
             address,
             features: Default::default(),
         };
 
-        chip.read_dynamic_chip()?;
+        chip.read_dynamic_chip(skipped)?;
 
         Ok(chip)
     }
 
-    fn read_dynamic_chip(&mut self) -> Result<(), ChipError> {
-        for entry in self
-            .path
-            .read_dir()?
-            .filter_map(Result::ok)
-            .filter(|entry| {
-                entry
-                    .file_type()
-                    .map(|ftype| ftype.is_file())
-                    .unwrap_or(false)
-            })
+    fn read_dynamic_chip(&mut self, skipped: &mut Vec<(PathBuf, Error)>) -> Result<(), ChipError> {
+        let fs = self.bus.context().fs().clone();
+
+        for path in fs
+            .read_dir(&self.path)?
+            .into_iter()
+            .filter(|path| fs.is_file(path))
         {
-            let path = entry.path();
+            match Subfeature::from_path_in(&fs, &path) {
+                Ok((feature_number, subfeature)) => {
+                    let feature_type = subfeature.get_type().feature_type();
+                    let feature_path = self.path.as_ref();
 
-            if let Ok((feature_number, subfeature)) = Subfeature::from_path(&path) {
-                let feature_type = FeatureType::from(subfeature.get_type());
-                let feature_path = self.path.as_ref();
+                    self.features
+                        .entry((feature_type, feature_number))
+                        .or_insert_with(|| {
+                            Feature::new(&fs, feature_path, feature_type, feature_number)
+                        })
+                        .push_subfeature(subfeature)
+                        .unwrap();
+                }
+                // Not named like a subfeature, like `name` or `uevent`
+                Err(SubfeatureError::Invalid) => log::debug!("Skip file {:?}", &path),
+                Err(err) => {
+                    log::debug!("Skip file {:?}: {}", &path, err);
+                    skipped.push((path, err.into()));
+                }
+            }
+        }
 
-                self.features
-                    .entry((feature_type, feature_number))
-                    .or_insert_with(|| Feature::new(feature_path, feature_type, feature_number))
-                    .push_subfeature(subfeature)
-                    .unwrap();
-            } else {
-                log::debug!("Skip file {:?}", &path);
+        #[cfg(feature = "sensorsconf")]
+        {
+            let chip_name: Rc<str> = Rc::from(self.name());
+            for feature in self.features.values_mut() {
+                let config =
+                    FeatureConfig::new(self.bus.context(), chip_name.clone(), feature.name());
+                feature.set_config(config);
             }
         }
 
@@ -175,39 +561,58 @@ impl Chip {
     }
 }
 
+/// Parse a decimal field of a device name, tagging a missing or malformed field
+/// with the bus type.
+fn parse_dec_field<T: FromStr>(field: Option<&&str>, bus_type: BusType) -> Result<T, ChipError> {
+    field
+        .and_then(|field| T::from_str(field).ok())
+        .ok_or(ChipError::ParseBusInfo(bus_type))
+}
+
+/// Parse an hexadecimal field of a device name, tagging a missing or malformed
+/// field with the bus type.
+fn parse_hex_field(field: Option<&&str>, bus_type: BusType) -> Result<u32, ChipError> {
+    field
+        .and_then(|field| u32::from_str_radix(field, 16).ok())
+        .ok_or(ChipError::ParseBusInfo(bus_type))
+}
+
+/// Same as `parse_hex_field`, rejecting the values which do not fit in `bits` bits.
+fn parse_hex_bits(field: Option<&&str>, bits: u32, bus_type: BusType) -> Result<u32, ChipError> {
+    let value = parse_hex_field(field, bus_type)?;
+    if value >> bits == 0 {
+        Ok(value)
+    } else {
+        Err(ChipError::ParseBusInfo(bus_type))
+    }
+}
+
 fn get_chip_bus_from_name(
     subsytem: &str,
     device_name: &str,
     context: &Context,
 ) -> Result<(Bus, u32), ChipError> {
-    let mut bus_type: BusType;
-    let mut bus_number: i16;
+    let mut bus_type = BusType::from_subsystem(subsytem).ok_or(ChipError::UnknownDevice)?;
+    let mut bus_number: i32;
     let address: u32;
 
-    match subsytem {
-        "i2c" => {
+    match bus_type {
+        BusType::I2C => {
             // Device name Regex: "^[[:digit:]]+-[[:xdigit:]]+$"
 
             let args: Vec<&str> = device_name.split('-').collect();
 
-            bus_number = i16::from_str(args.first().ok_or(ChipError::ParseBusInfo(BusType::SCSI))?)?;
-            address = u32::from_str_radix(
-                args.get(1).ok_or(ChipError::ParseBusInfo(BusType::SCSI))?,
-                16,
-            )?;
+            bus_number = parse_dec_field(args.first(), BusType::I2C)?;
+            address = parse_hex_field(args.get(1), BusType::I2C)?;
 
             // find out if legacy ISA or not
             if bus_number == 9191 {
                 bus_type = BusType::ISA;
                 bus_number = 0;
             } else {
-                bus_type = BusType::I2C;
-                let mut bus_path = PathBuf::from(SYSFS_MOUNT);
-                bus_path.push(format!("class/i2c-adapter/i2c-{}/device/name", bus_number));
+                let attr = format!("class/i2c-adapter/i2c-{}/device/name", bus_number);
 
-                if let Ok(mut bus_file) = std::fs::File::open(bus_path) {
-                    let mut bus_name: String = String::new();
-                    bus_file.read_to_string(&mut bus_name)?;
+                if let Ok(bus_name) = context.fs().read_attr(context.sysfs_root(), &attr) {
                     if bus_name == "ISA" {
                         bus_type = BusType::ISA;
                         bus_number = 0;
@@ -215,136 +620,746 @@ fn get_chip_bus_from_name(
                 }
             }
         }
-        "spi" => {
+        BusType::SPI => {
             // Device name Regex "^spi[[:digit:]]+\.[[:digit:]]+$"
 
-            let prefix = "spi";
-            if !device_name.starts_with(prefix) || (device_name.len() <= prefix.len()) {
-                return Err(ChipError::ParseBusInfo(BusType::SPI));
-            }
-            let (_, end) = device_name.split_at(prefix.len());
+            let end = device_name
+                .strip_prefix("spi")
+                .ok_or(ChipError::ParseBusInfo(BusType::SPI))?;
             let args: Vec<&str> = end.split('.').collect();
 
-            address = u32::from_str(args.get(1).ok_or(ChipError::ParseBusInfo(BusType::SPI))?)?;
-            bus_number = i16::from_str(args.first().ok_or(ChipError::ParseBusInfo(BusType::SPI))?)?;
-            bus_type = BusType::SPI;
+            address = parse_dec_field(args.get(1), BusType::SPI)?;
+            bus_number = parse_dec_field(args.first(), BusType::SPI)?;
         }
-        "pci" => {
+        BusType::PCI => {
             // Device name Regex: "^[[:xdigit:]]+:[[:xdigit:]]+:[[:xdigit:]]+\.[[:xdigit:]]+$"
 
             let args: Vec<&str> = device_name.split(':').collect();
-            let args_bis: Vec<&str> = args
-                .last()
-                .ok_or(ChipError::ParseBusInfo(BusType::PCI))?
-                .split('.')
-                .collect();
-
-            let _domain = u32::from_str_radix(
-                args.first().ok_or(ChipError::ParseBusInfo(BusType::SCSI))?,
-                16,
-            )?;
-            let _bus = u32::from_str_radix(
-                args.get(1).ok_or(ChipError::ParseBusInfo(BusType::SCSI))?,
-                16,
-            )?;
-            let _slot = u32::from_str_radix(
-                args_bis
-                    .first()
-                    .ok_or(ChipError::ParseBusInfo(BusType::SCSI))?,
-                16,
-            )?;
-            let _fn = u32::from_str_radix(
-                args_bis
-                    .get(1)
-                    .ok_or(ChipError::ParseBusInfo(BusType::SCSI))?,
-                16,
-            )?;
-
-            address = (_domain << 16) + (_bus << 8) + (_slot << 3) + _fn;
-            bus_type = BusType::PCI;
+            if args.len() != 3 {
+                return Err(ChipError::ParseBusInfo(BusType::PCI));
+            }
+            let args_bis: Vec<&str> = args[2].split('.').collect();
+
+            let _domain = parse_hex_bits(args.first(), 16, BusType::PCI)?;
+            let _bus = parse_hex_bits(args.get(1), 8, BusType::PCI)?;
+            let _slot = parse_hex_bits(args_bis.first(), 5, BusType::PCI)?;
+            let _fn = parse_hex_bits(args_bis.get(1), 3, BusType::PCI)?;
+
+            address = (_domain << 16) | (_bus << 8) | (_slot << 3) | _fn;
             bus_number = 0;
         }
-        "scsi" => {
+        BusType::SCSI => {
             // Device name Regex: "^[[:digit:]]+:[[:digit:]]+:[[:digit:]]+:[[:xdigit:]]+$"
 
             let args: Vec<&str> = device_name.split(':').collect();
 
-            let _bus = u32::from_str(args.get(1).ok_or(ChipError::ParseBusInfo(BusType::SCSI))?)?;
-            let _slot = u32::from_str(args.get(2).ok_or(ChipError::ParseBusInfo(BusType::SCSI))?)?;
-            let _fn = u32::from_str_radix(
-                args.get(3).ok_or(ChipError::ParseBusInfo(BusType::SCSI))?,
-                16,
-            )?;
+            let _bus: u32 = parse_dec_field(args.get(1), BusType::SCSI)?;
+            let _slot: u32 = parse_dec_field(args.get(2), BusType::SCSI)?;
+            let _fn = parse_hex_field(args.get(3), BusType::SCSI)?;
 
             address = (_bus << 8) + (_slot << 4) + _fn;
-            bus_number = i16::from_str(args.first().ok_or(ChipError::ParseBusInfo(BusType::SCSI))?)?;
-            bus_type = BusType::SCSI;
+            bus_number = parse_dec_field(args.first(), BusType::SCSI)?;
         }
-        "platform" | "of_platform" => {
+        BusType::ISA => {
             let args: Vec<&str> = device_name.split(':').collect();
 
             address = args
                 .get(1)
                 .map_or(0, |addr| u32::from_str(addr).unwrap_or(0));
-            bus_type = BusType::ISA;
-            bus_number = 0;
-        }
-        "acpi" => {
-            bus_type = BusType::ACPI;
-            bus_number = 0;
-            address = 0;
-        }
-        "hid" => {
-            bus_type = BusType::HID;
             bus_number = 0;
-            address = 0;
         }
-        "mdio_bus" => {
-            bus_type = BusType::MDIO;
+        BusType::ACPI | BusType::HID | BusType::MDIO => {
             bus_number = 0;
             address = 0;
         }
-        _ => return Err(ChipError::UnknownDevice),
+        // Virtual devices have no subsystem
+        BusType::Virtual => return Err(ChipError::UnknownDevice),
     }
 
     Ok((Bus::new(bus_type, bus_number, context.clone()), address))
 }
 
 pub fn read_sysfs_chips(context: &Context) -> Result<Vec<Chip>, Error> {
-    let mut hwmon_path = PathBuf::from(SYSFS_MOUNT);
+    read_sysfs_chips_filtered(context, |_| true)
+}
+
+/// Same as `read_sysfs_chips`, keeping only the chips for which `predicate` returns
+/// `true`.
+pub fn read_sysfs_chips_filtered<P>(context: &Context, predicate: P) -> Result<Vec<Chip>, Error>
+where
+    P: Fn(&Chip) -> bool,
+{
+    sysfs_check_root(context.fs().as_ref(), context.sysfs_root())?;
+
+    let mut hwmon_path = context.sysfs_root().to_owned();
     hwmon_path.push("class/hwmon");
 
-    let mut chips: Vec<Chip> = Vec::new();
+    read_chips_in(&hwmon_path, context, predicate, &mut Vec::new())
+}
 
-    for entry in std::fs::read_dir(hwmon_path)? {
-        let entry = entry?;
-        let path = entry.path();
+/// Same as `read_sysfs_chips`, also returning what was skipped during the scan: the
+/// chips which can not be read, and the files of the chips which look like subfeatures
+/// but can not be read as one, like an unknown `temp1_foo`. Each of them comes with
+/// the reason it was skipped.
+#[allow(clippy::type_complexity)]
+pub fn read_sysfs_chips_strict(
+    context: &Context,
+) -> Result<(Vec<Chip>, Vec<(PathBuf, Error)>), Error> {
+    sysfs_check_root(context.fs().as_ref(), context.sysfs_root())?;
 
+    let mut hwmon_path = context.sysfs_root().to_owned();
+    hwmon_path.push("class/hwmon");
+
+    let mut skipped = Vec::new();
+    let chips = read_chips_in(&hwmon_path, context, |_| true, &mut skipped)?;
+    Ok((chips, skipped))
+}
+
+fn read_chips_in<P>(
+    hwmon_path: &Path,
+    context: &Context,
+    predicate: P,
+    skipped: &mut Vec<(PathBuf, Error)>,
+) -> Result<Vec<Chip>, Error>
+where
+    P: Fn(&Chip) -> bool,
+{
+    let fs = context.fs();
+    let mut chips: Vec<Chip> = Vec::new();
+
+    for path in fs.read_dir(hwmon_path)? {
         let mut link_path = path.clone();
         link_path.push("device");
-        let chip = if link_path.read_link().is_ok() {
+        let mut chip_skipped = Vec::new();
+        let chip = if fs.read_link(&link_path).is_ok() {
             log::debug!("{:?}.read_link() -> Ok", link_path);
 
             // The attributes we want might be those of the hwmon class
             // device, or those of the device itself.
-            match Chip::from_path(path.as_ref(), link_path.as_ref(), context) {
+            match Chip::from_path_skipping(&path, Some(&link_path), context, &mut chip_skipped) {
                 Ok(chip) => Ok(chip),
                 Err(e) => {
                     log::debug!("{:?}", e);
-                    Chip::from_path(link_path.as_ref(), link_path.as_ref(), context)
+                    chip_skipped.clear();
+                    Chip::from_path_skipping(
+                        &link_path,
+                        Some(&link_path),
+                        context,
+                        &mut chip_skipped,
+                    )
                 }
             }
         } else {
             // No device link? Treat as virtual
             log::debug!("{:?}.read_link() -> Err", link_path);
-            Chip::from_path(path.as_ref(), None, context)
+            Chip::from_path_skipping(&path, None, context, &mut chip_skipped)
         };
 
-        if let Ok(chip) = chip {
-            log::debug!("Add chip '{}'", chip.name());
-            chips.push(chip);
+        match chip {
+            Ok(chip) if predicate(&chip) => {
+                log::debug!("Add chip '{}'", chip.name());
+                chips.push(chip);
+                skipped.append(&mut chip_skipped);
+            }
+            Ok(chip) => log::debug!("Skip chip '{}'", chip.name()),
+            Err(err) => skipped.push((path, err.into())),
         }
     }
 
     Ok(chips)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{get_chip_bus_from_name, read_chips_in, read_sysfs_chips_strict, Chip};
+    use crate::bus::BusType;
+    use crate::context::Context;
+    use crate::error::{ChipError, Error};
+    use crate::feature::FeatureType;
+    use crate::subfeature::*;
+    use crate::sysfs::sysfs_read_file;
+    use crate::testutil::{fake_chip, TempDir};
+    use crate::unit::Unit;
+
+    #[test]
+    fn chip_driver_from_symlink() {
+        let (dir, chip) = fake_chip("coretemp", &[("temp1_input", "45000")]);
+        assert_eq!(chip.driver(), None);
+
+        dir.symlink("device/driver", "../../../bus/platform/drivers/coretemp");
+        assert_eq!(chip.driver().as_deref(), Some("coretemp"));
+    }
+
+    #[test]
+    fn chip_driver_from_modalias() {
+        let (dir, chip) = fake_chip(
+            "lm75",
+            &[("temp1_input", "45000"), ("device/modalias", "i2c:lm75")],
+        );
+        assert_eq!(chip.driver().as_deref(), Some("lm75"));
+
+        for (modalias, driver) in [
+            ("platform:it87", Some("it87")),
+            ("spi:tmp121", Some("tmp121")),
+            ("pci:v00001022d00001463sv0000sd0000bc06sc00i00", None),
+            ("acpi:PNP0C0B:", None),
+            ("lm75", None),
+        ] {
+            dir.file("device/modalias", modalias);
+            assert_eq!(chip.driver().as_deref(), driver, "{}", modalias);
+        }
+    }
+
+    #[test]
+    fn chip_fan_pwm_links_index_match() {
+        let (_dir, chip) = fake_chip(
+            "nct6775",
+            &[
+                ("fan1_input", "1200"),
+                ("fan2_input", "800"),
+                ("fan3_input", "0"),
+                ("pwm1", "128"),
+                ("pwm2", "255"),
+                ("pwm4", "0"),
+            ],
+        );
+
+        assert_eq!(chip.fan_pwm_links(), vec![(1, 1), (2, 2)]);
+    }
+
+    #[test]
+    fn chip_read_snapshot() {
+        let (_dir, chip) = fake_chip(
+            "dummy",
+            &[
+                ("temp1_input", "45000"),
+                ("temp1_crit", "garbage"),
+                ("in0_input", "1200"),
+                ("fan1_input", "1250"),
+                ("fan1_min", "600"),
+            ],
+        );
+
+        let snapshot = chip.read_snapshot();
+        assert_eq!(snapshot.len(), 3);
+
+        let temp = &snapshot[&(FeatureType::Temperature, 1)];
+        assert_eq!(
+            temp[&SubfeatureType::Temperature(Temperature::Input)]
+                .as_ref()
+                .ok(),
+            Some(&45.0)
+        );
+        assert!(matches!(
+            temp[&SubfeatureType::Temperature(Temperature::Crit_Max)],
+            Err(Error::ParseFloat(_))
+        ));
+
+        let volt = &snapshot[&(FeatureType::Voltage, 0)];
+        assert_eq!(
+            volt[&SubfeatureType::Voltage(Voltage::Input)].as_ref().ok(),
+            Some(&1.2)
+        );
+
+        let fan = &snapshot[&(FeatureType::Fan, 1)];
+        assert_eq!(fan.len(), 2);
+        assert_eq!(
+            fan[&SubfeatureType::Fan(Fan::Min)].as_ref().ok(),
+            Some(&600.0)
+        );
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn chip_read_snapshot_async() {
+        let (_dir, chip) = fake_chip(
+            "dummy",
+            &[
+                ("temp1_input", "45000"),
+                ("temp1_crit", "garbage"),
+                ("fan1_input", "1250"),
+            ],
+        );
+
+        let snapshot = chip.read_snapshot_async().await;
+        assert_eq!(snapshot.len(), 2);
+        assert!(matches!(
+            snapshot[&(FeatureType::Temperature, 1)]
+                [&SubfeatureType::Temperature(Temperature::Crit_Max)],
+            Err(Error::ParseFloat(_))
+        ));
+        assert_eq!(
+            snapshot[&(FeatureType::Fan, 1)][&SubfeatureType::Fan(Fan::Input)]
+                .as_ref()
+                .ok(),
+            Some(&1250.0)
+        );
+    }
+
+    #[test]
+    fn chip_feature_lookup() {
+        let (_dir, chip) = fake_chip(
+            "dummy",
+            &[
+                ("temp3_input", "50000"),
+                ("temp1_input", "45000"),
+                ("temp1_max", "80000"),
+                ("temp1_label", "Core 0\n"),
+                ("fan1_input", "1250"),
+                ("fan1_min", "600"),
+                ("fan1_alarm", "0"),
+                ("in0_input", "1200"),
+            ],
+        );
+
+        assert_eq!(chip.feature_numbers(FeatureType::Temperature), vec![1, 3]);
+        assert_eq!(chip.feature_numbers(FeatureType::Voltage), vec![0]);
+        assert!(chip.feature_numbers(FeatureType::Power).is_empty());
+
+        let mut iter = chip.features_iter();
+        assert_eq!(iter.len(), 4);
+        assert_eq!(iter.next_back().unwrap().name(), "in0");
+        assert_eq!(iter.len(), 3);
+
+        let names: Vec<&str> = chip
+            .features_of_type(FeatureType::Temperature)
+            .map(|feature| feature.name())
+            .collect();
+        assert_eq!(names, ["temp1", "temp3"]);
+        assert_eq!(chip.features_of_type(FeatureType::Power).count(), 0);
+
+        assert_eq!(chip.read_attr("temp1_label").unwrap(), "Core 0");
+        assert_eq!(
+            chip.read_attr("vendor").unwrap_err().kind(),
+            std::io::ErrorKind::NotFound
+        );
+
+        assert_eq!(chip.feature_count(), 4);
+        assert_eq!(chip.subfeature_count(), 7);
+
+        let (_dir, chip) = fake_chip("dummy", &[]);
+        assert_eq!(chip.feature_count(), 0);
+        assert_eq!(chip.subfeature_count(), 0);
+    }
+
+    #[test]
+    fn chip_parse_name() {
+        assert_eq!(
+            Chip::parse_name("k10temp-pci-00c3"),
+            Some((String::from("k10temp"), BusType::PCI, 0, 0xc3))
+        );
+        assert_eq!(
+            Chip::parse_name("lm75-i2c-1-48"),
+            Some((String::from("lm75"), BusType::I2C, 1, 0x48))
+        );
+        assert_eq!(
+            Chip::parse_name("coretemp-isa-0000"),
+            Some((String::from("coretemp"), BusType::ISA, 0, 0))
+        );
+        assert_eq!(
+            Chip::parse_name("it87-isa-0290"),
+            Some((String::from("it87"), BusType::ISA, 0, 0x290))
+        );
+        assert_eq!(
+            Chip::parse_name("dell-smm-virtual-0"),
+            Some((String::from("dell-smm"), BusType::Virtual, 0, 0))
+        );
+
+        let (_dir, chip) = fake_chip("dummy", &[("temp1_input", "45000")]);
+        let (prefix, bus_type, bus_number, address) = Chip::parse_name(&chip.name()).unwrap();
+        assert_eq!(prefix, chip.prefix());
+        assert_eq!(bus_type, chip.bus().get_type());
+        assert_eq!(bus_number, chip.bus().number());
+        assert_eq!(address, chip.address());
+
+        assert_eq!(Chip::parse_name("lm75"), None);
+        assert_eq!(Chip::parse_name("-isa-0290"), None);
+        assert_eq!(Chip::parse_name("lm75-i2c-x-48"), None);
+        assert_eq!(Chip::parse_name("lm75-usb-1-48"), None);
+        assert_eq!(Chip::parse_name("it87-isa-zz"), None);
+    }
+
+    #[test]
+    fn chip_inputs() {
+        let (dir, chip) = fake_chip(
+            "dummy",
+            &[
+                ("temp1_input", "45000"),
+                ("temp1_label", "CPU"),
+                ("temp1_max", "80000"),
+                ("fan1_input", "1250"),
+                ("in0_input", "1200"),
+                ("intrusion0_alarm", "0"),
+            ],
+        );
+        std::fs::remove_file(dir.path().join("in0_input")).unwrap();
+
+        assert_eq!(
+            chip.inputs(),
+            vec![
+                ("fan1".to_owned(), 1250.0, Unit::Rpm),
+                ("CPU".to_owned(), 45.0, Unit::Celsius),
+            ]
+        );
+    }
+
+    #[test]
+    fn chip_max_temperature_and_alarms() {
+        let (dir, chip) = fake_chip(
+            "dummy",
+            &[
+                ("temp1_input", "45000"),
+                ("temp2_input", "62500"),
+                ("temp3_input", "127000"),
+                ("temp3_fault", "1"),
+                ("temp2_max_alarm", "0"),
+                ("in0_input", "1200"),
+                ("in0_alarm", "0"),
+            ],
+        );
+        assert_eq!(chip.max_temperature(), Some(62.5));
+        assert!(!chip.any_alarm_active());
+
+        dir.file("in0_alarm", "1");
+        assert!(chip.any_alarm_active());
+
+        let (_dir, chip) = fake_chip("dummy", &[("in0_input", "1200")]);
+        assert_eq!(chip.max_temperature(), None);
+    }
+
+    #[test]
+    fn chip_writable_subfeatures() {
+        let dir = TempDir::new();
+        dir.file("name", "nct6775");
+        dir.file_with_mode("pwm1", "128", 0o644);
+        dir.file_with_mode("pwm1_mode", "1", 0o444);
+        dir.file_with_mode("fan1_input", "1200", 0o444);
+        dir.file_with_mode("temp1_input", "45000", 0o444);
+        let chip = Chip::from_path(dir.path(), None, &Context::empty()).unwrap();
+
+        let names: Vec<&str> = chip.writable_subfeatures().map(Subfeature::name).collect();
+        assert_eq!(names, vec!["pwm1"]);
+
+        let pwm = chip.feature(FeatureType::Pwm, 1).unwrap();
+        let names: Vec<&str> = pwm.writable_subfeatures().map(Subfeature::name).collect();
+        assert_eq!(names, vec!["pwm1"]);
+        let fan = chip.feature(FeatureType::Fan, 1).unwrap();
+        assert_eq!(fan.writable_subfeatures().count(), 0);
+    }
+
+    #[test]
+    fn chip_set_beep_enable() {
+        let (dir, chip) = fake_chip("dummy", &[("beep_enable", "1"), ("temp1_input", "45000")]);
+        let path = dir.path().join("beep_enable");
+
+        chip.set_beep_enable(false).unwrap();
+        assert_eq!(sysfs_read_file(&path).unwrap(), "0");
+        chip.set_beep_enable(true).unwrap();
+        assert_eq!(sysfs_read_file(&path).unwrap(), "1");
+
+        let (_dir, chip) = fake_chip("dummy", &[("temp1_input", "45000")]);
+        assert!(matches!(
+            chip.set_beep_enable(true),
+            Err(Error::SubfeatureType)
+        ));
+    }
+
+    #[test]
+    fn chip_update_interval() {
+        let (dir, chip) = fake_chip(
+            "dummy",
+            &[("temp1_input", "45000"), ("update_interval", "500")],
+        );
+        assert_eq!(chip.update_interval().unwrap().unwrap(), 500.0);
+
+        chip.set_update_interval(1000.0).unwrap();
+        assert_eq!(
+            sysfs_read_file(&dir.path().join("update_interval")).unwrap(),
+            "1000"
+        );
+        assert_eq!(chip.update_interval().unwrap().unwrap(), 1000.0);
+        assert!(matches!(
+            chip.set_update_interval(-1.0),
+            Err(Error::InvalidValue)
+        ));
+
+        let (_dir, chip) = fake_chip("dummy", &[("temp1_input", "45000")]);
+        assert!(chip.update_interval().is_none());
+    }
+
+    #[test]
+    fn chip_subfeature_by_path() {
+        let (dir, chip) = fake_chip(
+            "dummy",
+            &[
+                ("temp1_input", "45000"),
+                ("temp1_crit", "100000"),
+                ("in0_input", "1200"),
+            ],
+        );
+
+        let subfeature = chip
+            .subfeature_by_path(&dir.path().join("temp1_crit"))
+            .unwrap();
+        assert_eq!(
+            subfeature.get_type(),
+            SubfeatureType::Temperature(Temperature::Crit_Max)
+        );
+
+        let path = dir.path().join("./in0_input");
+        let subfeature = chip.subfeature_by_path(&path).unwrap();
+        assert_eq!(
+            subfeature.get_type(),
+            SubfeatureType::Voltage(Voltage::Input)
+        );
+
+        // The chip directory seen from its device, like
+        // `/sys/class/hwmon/hwmon0/device/hwmon/hwmon0`.
+        let device = TempDir::new();
+        device.symlink("hwmon/hwmon0", dir.path().to_str().unwrap());
+        dir.symlink("device", device.path().to_str().unwrap());
+        let path = dir.path().join("device/hwmon/hwmon0/temp1_crit");
+        let subfeature = chip.subfeature_by_path(&path).unwrap();
+        assert_eq!(
+            subfeature.get_type(),
+            SubfeatureType::Temperature(Temperature::Crit_Max)
+        );
+        assert!(chip
+            .subfeature_by_path(&dir.path().join("device/temp1_crit"))
+            .is_none());
+
+        assert!(chip
+            .subfeature_by_path(&dir.path().join("temp2_input"))
+            .is_none());
+    }
+
+    fn parse_bus_info_error(subsystem: &str, device_name: &str) -> Option<BusType> {
+        match get_chip_bus_from_name(subsystem, device_name, &Context::empty()) {
+            Err(ChipError::ParseBusInfo(bus_type)) => Some(bus_type),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn chip_unparseable_bus_error() {
+        let mut mock = crate::mock::MockFs::new();
+        mock.dir("/sys/class/i2c-adapter");
+        mock.file("/sys/class/hwmon/hwmon0/name", "lm75");
+        mock.symlink(
+            "/sys/class/hwmon/hwmon0/device/subsystem",
+            "../../../../bus/i2c",
+        );
+        mock.symlink(
+            "/sys/class/hwmon/hwmon0/device",
+            "../../../devices/i2c-0/0-zz",
+        );
+        let context = Context::with_mock(mock).unwrap();
+
+        let path = std::path::Path::new("/sys/class/hwmon/hwmon0");
+        let err: Error = Chip::from_path(path, path.join("device").as_path(), &context)
+            .unwrap_err()
+            .into();
+        assert!(matches!(err, Error::Chip(_)));
+        assert_eq!(err.to_string(), "Chip error: Failed to read I2C bus info");
+    }
+
+    #[test]
+    fn chip_bus_from_name_pci() {
+        let (bus, address) =
+            get_chip_bus_from_name("pci", "0000:00:18.3", &Context::empty()).unwrap();
+        assert_eq!(bus.get_type(), BusType::PCI);
+        assert_eq!(address, 0xc3);
+
+        assert_eq!(
+            parse_bus_info_error("pci", "0000:00:18"),
+            Some(BusType::PCI)
+        );
+        assert_eq!(
+            parse_bus_info_error("pci", "0000:zz:18.3"),
+            Some(BusType::PCI)
+        );
+
+        let (_, address) =
+            get_chip_bus_from_name("pci", "ffff:ff:1f.7", &Context::empty()).unwrap();
+        assert_eq!(address, u32::MAX);
+        for device_name in [
+            "10000:00:18.3",
+            "0000:100:18.3",
+            "0000:00:20.3",
+            "0000:00:18.8",
+        ] {
+            assert_eq!(parse_bus_info_error("pci", device_name), Some(BusType::PCI));
+        }
+    }
+
+    #[test]
+    fn chip_bus_from_name_scsi() {
+        let (bus, address) = get_chip_bus_from_name("scsi", "2:0:1:a", &Context::empty()).unwrap();
+        assert_eq!(bus.get_type(), BusType::SCSI);
+        assert_eq!(bus.number(), 2);
+        assert_eq!(address, 0x1a);
+
+        assert_eq!(parse_bus_info_error("scsi", "2:0:1"), Some(BusType::SCSI));
+        assert_eq!(parse_bus_info_error("scsi", "x:0:1:a"), Some(BusType::SCSI));
+    }
+
+    #[test]
+    fn chip_bus_from_name_i2c() {
+        let (bus, address) =
+            get_chip_bus_from_name("i2c", "40000-0048", &Context::empty()).unwrap();
+        assert_eq!(bus.get_type(), BusType::I2C);
+        assert_eq!(bus.number(), 40000);
+        assert_eq!(address, 0x48);
+
+        assert_eq!(parse_bus_info_error("i2c", "1"), Some(BusType::I2C));
+        assert_eq!(parse_bus_info_error("i2c", "x-0048"), Some(BusType::I2C));
+        assert_eq!(parse_bus_info_error("i2c", "1-zz"), Some(BusType::I2C));
+    }
+
+    #[test]
+    fn chip_bus_from_name_spi() {
+        let (bus, address) = get_chip_bus_from_name("spi", "spi1.2", &Context::empty()).unwrap();
+        assert_eq!(bus.get_type(), BusType::SPI);
+        assert_eq!(bus.number(), 1);
+        assert_eq!(address, 2);
+
+        assert_eq!(parse_bus_info_error("spi", "1.2"), Some(BusType::SPI));
+        assert_eq!(parse_bus_info_error("spi", "spi1"), Some(BusType::SPI));
+        assert_eq!(parse_bus_info_error("spi", "spix.2"), Some(BusType::SPI));
+    }
+
+    fn chip_on_bus(subsystem: &str, device_name: &str) -> Chip {
+        let (bus, address) =
+            get_chip_bus_from_name(subsystem, device_name, &Context::empty()).unwrap();
+        Chip {
+            path: Default::default(),
+            canonical_path: Default::default(),
+            prefix: "dummy".to_owned(),
+            bus,
+            address,
+            features: Default::default(),
+        }
+    }
+
+    #[test]
+    fn chip_address_string_pci() {
+        assert_eq!(
+            chip_on_bus("pci", "0000:00:18.3").address_string(),
+            "0000:00:18.3"
+        );
+        assert_eq!(
+            chip_on_bus("pci", "0001:3a:1f.7").address_string(),
+            "0001:3a:1f.7"
+        );
+    }
+
+    #[test]
+    fn chip_address_string_i2c() {
+        assert_eq!(chip_on_bus("i2c", "1-002a").address_string(), "0x2a");
+        assert_eq!(chip_on_bus("i2c", "9191-0290").address_string(), "0x0290");
+    }
+
+    #[test]
+    fn chip_name_i2c() {
+        // Lowercase like lm-sensors, so the config chip globs like `lm75-i2c-*` match.
+        let chip = chip_on_bus("i2c", "1-002a");
+        assert_eq!(chip.name(), "dummy-i2c-1-2a");
+        assert_eq!(
+            Chip::parse_name(&chip.name()),
+            Some((String::from("dummy"), BusType::I2C, 1, 0x2a))
+        );
+    }
+
+    #[test]
+    fn chip_address_string_scsi() {
+        assert_eq!(chip_on_bus("scsi", "2:0:1:a").address_string(), "2:0:1:a");
+    }
+
+    #[test]
+    fn read_chips_filtered_by_bus() {
+        let dir = TempDir::new();
+        dir.file("class/hwmon/hwmon0/name", "acpitz");
+        dir.file("class/hwmon/hwmon0/temp1_input", "27800");
+        dir.file("class/hwmon/hwmon1/name", "k10temp");
+        dir.file("class/hwmon/hwmon1/temp1_input", "45000");
+        dir.symlink(
+            "class/hwmon/hwmon1/device",
+            "../../../devices/pci0000:00/0000:00:18.3",
+        );
+        dir.symlink(
+            "devices/pci0000:00/0000:00:18.3/subsystem",
+            "../../../bus/pci",
+        );
+
+        let hwmon_path = dir.path().join("class/hwmon");
+        let mut chips =
+            read_chips_in(&hwmon_path, &Context::empty(), |_| true, &mut Vec::new()).unwrap();
+        chips.sort_by_key(Chip::name);
+        assert_eq!(chips.len(), 2);
+        assert!(chips[0].is_virtual());
+        assert!(!chips[1].is_virtual());
+
+        let chips = read_chips_in(
+            &hwmon_path,
+            &Context::empty(),
+            |chip| chip.bus().get_type() == BusType::PCI,
+            &mut Vec::new(),
+        )
+        .unwrap();
+        assert_eq!(chips.len(), 1);
+        assert_eq!(chips[0].name(), "k10temp-pci-00c3");
+    }
+
+    #[test]
+    fn read_chips_strict_skipped_files() {
+        let mut mock = crate::mock::MockFs::new();
+        mock.dir("/sys/class/i2c-adapter");
+        mock.file("/sys/class/hwmon/hwmon0/name", "dummy");
+        mock.file("/sys/class/hwmon/hwmon0/uevent", "");
+        mock.file("/sys/class/hwmon/hwmon0/temp1_input", "45000");
+        mock.file("/sys/class/hwmon/hwmon0/temp1_foo", "1");
+        mock.dir("/sys/class/hwmon/hwmon1");
+        let context = Context::with_mock(mock).unwrap();
+
+        let (chips, skipped) = read_sysfs_chips_strict(&context).unwrap();
+        assert_eq!(chips.len(), 1);
+        assert_eq!(chips[0].features_iter().count(), 1);
+
+        let skipped: Vec<(&Path, String)> = skipped
+            .iter()
+            .map(|(path, err)| (path.as_path(), err.to_string()))
+            .collect();
+        assert_eq!(
+            skipped,
+            [
+                (
+                    Path::new("/sys/class/hwmon/hwmon0/temp1_foo"),
+                    String::from("Feature error: Unknown subfeature")
+                ),
+                (
+                    Path::new("/sys/class/hwmon/hwmon1"),
+                    String::from("Chip error: IO error: entity not found")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn chip_stable_id_and_eq() {
+        let (_dir1, chip1) = fake_chip("dummy", &[("temp1_input", "45000")]);
+        let (_dir2, chip2) = fake_chip("dummy", &[("temp1_input", "50000")]);
+        assert_ne!(chip1.path(), chip2.path());
+        assert_eq!(chip1.stable_id(), "dummy:Virtual:0:0");
+        assert_eq!(chip1, chip2);
+
+        let mut lm75 = chip_on_bus("i2c", "1-0048");
+        assert_eq!(lm75.stable_id(), "dummy:I2C:1:48");
+        lm75.path = chip1.path().to_owned();
+        assert_eq!(lm75, chip_on_bus("i2c", "1-0048"));
+        assert_ne!(lm75, chip_on_bus("i2c", "1-0049"));
+        assert_ne!(lm75, chip_on_bus("i2c", "2-0048"));
+        assert_ne!(lm75, chip1);
+    }
+}
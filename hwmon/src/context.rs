@@ -1,38 +1,672 @@
 // SPDX-FileCopyrightText: 2018 Camille019
 // SPDX-License-Identifier: MPL-2.0
 
+use std::cell::OnceCell;
+#[cfg(feature = "sensorsconf")]
+use std::cell::{Ref, RefCell};
+use std::io;
+use std::ops::ControlFlow;
 use std::path::Path;
+#[cfg(feature = "sensorsconf")]
+use std::path::PathBuf;
 use std::rc::Rc;
+use std::thread;
+use std::time::Duration;
 
 use crate::bus::{self, BusAdapter};
+use crate::chip::{self, Chip};
 use crate::error::*;
+use crate::mock::MockFs;
+#[cfg(feature = "sensorsconf")]
+use crate::parser::{self, CfgFile, Expr, StmtCompute};
+use crate::sysfs::{Fs, StdFs, SYSFS_MOUNT};
 
-#[derive(Clone)]
+/// Configuration files read when none is given to `Context::new`, by order of preference.
+#[cfg(feature = "sensorsconf")]
+const DEFAULT_CONFIG_FILES: [&str; 2] = ["/etc/sensors3.conf", "/etc/sensors.conf"];
+
+/// Directory of additional configuration files, read in alphabetical order after the
+/// default configuration file.
+#[cfg(feature = "sensorsconf")]
+const DEFAULT_CONFIG_DIR: &str = "/etc/sensors.d";
+
+/// Value converted back and forth to check the compute statements of the configuration.
+#[cfg(feature = "sensorsconf")]
+const COMPUTE_CHECK_SAMPLE: f32 = 1.0;
+
+/// Number of times `watch_chips` scans again when a file vanishes during a scan.
+const WATCH_RETRIES: u32 = 3;
+
+/// Delay before the first scan again of `watch_chips`, doubled for each retry.
+const WATCH_RETRY_DELAY: Duration = Duration::from_millis(10);
+
+#[derive(Clone, Debug)]
 pub struct Context {
+    /// Mount point of sysfs, `/sys` unless the context is built over another tree.
+    sysfs_root: Rc<Path>,
+    /// File system the sysfs tree is read from, the real one unless the context is
+    /// built over a `MockFs`.
+    fs: Fs,
     adapters: Rc<Vec<BusAdapter>>,
+    /// Chips found by the first call to `chips`. The cache belongs to this value, with
+    /// the clones made after the scan sharing the same chips. The chips themselves
+    /// hold clones made before the scan, with an empty cache, so there is no `Rc` cycle.
+    chips: OnceCell<Rc<Vec<Chip>>>,
+    /// The configuration is shared by all the clones of the context, including the
+    /// ones held by chips, features and subfeatures. It is behind a `RefCell` so that
+    /// `reload_config` is seen by all of them. The borrows are short-lived and never
+    /// held across calls, so they can not conflict.
+    #[cfg(feature = "sensorsconf")]
+    config: Rc<RefCell<Config>>,
+}
+
+#[cfg(feature = "sensorsconf")]
+#[derive(Debug, Default)]
+struct Config {
+    /// Configuration file given by the user, if any.
+    file: Option<PathBuf>,
+    cfg: CfgFile,
+}
+
+#[cfg(feature = "sensorsconf")]
+impl Config {
+    fn load(file: Option<&Path>) -> Result<Config, Error> {
+        let cfg = match file {
+            Some(path) => parser::parse_configuration_file(path)?,
+            None => {
+                let mut cfg = match DEFAULT_CONFIG_FILES
+                    .iter()
+                    .map(Path::new)
+                    .find(|path| path.is_file())
+                {
+                    Some(path) => parser::parse_configuration_file(path)?,
+                    None => CfgFile::default(),
+                };
+                for path in config_dir_files(Path::new(DEFAULT_CONFIG_DIR)) {
+                    cfg.merge(parser::parse_configuration_file(path)?);
+                }
+                cfg
+            }
+        };
+
+        for warning in non_invertible_computes(&cfg) {
+            log::warn!("{}", warning);
+        }
+
+        Ok(Config {
+            file: file.map(Path::to_owned),
+            cfg,
+        })
+    }
+}
+
+/// Return a warning for each compute statement of the configuration whose two
+/// expressions are not the inverse of each other.
+#[cfg(feature = "sensorsconf")]
+fn non_invertible_computes(cfg: &CfgFile) -> Vec<String> {
+    cfg.computes()
+        .filter(|(_, compute)| !compute.check_invertible(COMPUTE_CHECK_SAMPLE))
+        .map(|(chip_names, compute)| {
+            format!(
+                "Compute statement of {} for chip {} is not invertible, writes will be wrong",
+                compute.name(),
+                chip_names.join(" "),
+            )
+        })
+        .collect()
+}
+
+/// List the regular files of a configuration directory in alphabetical order,
+/// skipping hidden files. A missing directory is not an error.
+#[cfg(feature = "sensorsconf")]
+fn config_dir_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = match dir.read_dir() {
+        Ok(entries) => entries
+            .filter_map(Result::ok)
+            .filter(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file())
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    files.sort();
+    files
 }
 
 impl Context {
+    /// Create a context over the sysfs mounted at `/sys`.
+    ///
+    /// Return `Error::SysfsUnavailable` if sysfs is not mounted, as in some containers.
     pub fn new<'a, T: Into<Option<&'a Path>>>(config_file: T) -> Result<Context, Error> {
+        Context::from_sysfs_root(SYSFS_MOUNT, config_file.into())
+    }
 
-        #[cfg(feature = "sensorsconf")]
-        let config_file = config_file.into();
+    /// Create a context over the sysfs tree at `root`, like a fixture tree in tests or
+    /// the host sysfs mounted elsewhere in a container.
+    ///
+    /// Return `Error::SysfsUnavailable` if `root` does not look like a sysfs tree.
+    pub fn from_sysfs_root<P: AsRef<Path>>(
+        root: P,
+        config_file: Option<&Path>,
+    ) -> Result<Context, Error> {
         #[cfg(not(feature = "sensorsconf"))]
-        let _config_file = config_file.into();
-
-        let adapters = Rc::new(bus::read_sysfs_busses()?);
+        let _ = config_file;
 
+        let context = Context::build(StdFs::shared(), root.as_ref())?;
         #[cfg(feature = "sensorsconf")]
-        if let Some(path) = config_file {
-            unimplemented!()
-        } else {
-            unimplemented!()
+        context.config.replace(Config::load(config_file)?);
+        Ok(context)
+    }
+
+    /// Create a context over an in-memory sysfs tree mounted at `/sys`, for tests and
+    /// examples.
+    ///
+    /// No configuration file is read. Return `Error::SysfsUnavailable` if the tree has
+    /// no `/sys/class` directory.
+    pub fn with_mock(mock: MockFs) -> Result<Context, Error> {
+        Context::build(Rc::new(mock), Path::new(SYSFS_MOUNT))
+    }
+
+    fn build(fs: Fs, sysfs_root: &Path) -> Result<Context, Error> {
+        let adapters = Rc::new(bus::read_sysfs_busses(fs.as_ref(), sysfs_root)?);
+
+        Ok(Context {
+            sysfs_root: Rc::from(sysfs_root),
+            fs,
+            adapters,
+            chips: OnceCell::new(),
+            #[cfg(feature = "sensorsconf")]
+            config: Default::default(),
+        })
+    }
+
+    /// Return the chips of the system.
+    ///
+    /// The first call scans sysfs like `read_sysfs_chips`, the next ones return the
+    /// same chips without scanning again, until `invalidate` is called.
+    pub fn chips(&self) -> Result<&[Chip], Error> {
+        self.chips_with(chip::read_sysfs_chips)
+    }
+
+    /// Return the chip whose name is `name`, in the `prefix-bus-address` form returned
+    /// by `Chip::name`, like `lm75-i2c-1-48`, or `None` if there is no such chip.
+    ///
+    /// The chips are the ones of `chips`, scanned by the first call.
+    pub fn chip_by_name(&self, name: &str) -> Result<Option<&Chip>, Error> {
+        Ok(self.chips()?.iter().find(|chip| chip.name() == name))
+    }
+
+    /// Scan the chips every `interval` and call `callback` with the chips found, until
+    /// it returns `ControlFlow::Break`. The callback returns a `ControlFlow` so that the
+    /// watch can be stopped, it would never return otherwise.
+    ///
+    /// The chips are scanned again each time, without the cache of `chips`, so that
+    /// hotplugged chips, like USB sensors, appear and disappear. A chip vanishing
+    /// during a scan is skipped, and a missing `class/hwmon` directory is seen as no
+    /// chips. A file vanishing in the middle of a scan makes it start again, up to
+    /// three times with a doubling delay from 10 ms, and the callback is not called for
+    /// this round if the file is still missing.
+    ///
+    /// This blocks the calling thread, it is meant to run on a thread of its own, with
+    /// the context built on that thread.
+    pub fn watch_chips<F>(&self, interval: Duration, mut callback: F) -> Result<(), Error>
+    where
+        F: FnMut(&[Chip]) -> ControlFlow<()>,
+    {
+        loop {
+            if let Some(chips) = scan_retrying(|| chip::read_sysfs_chips(self))? {
+                if callback(&chips).is_break() {
+                    return Ok(());
+                }
+            }
+            thread::sleep(interval);
         }
+    }
 
-        Ok(Context { adapters })
+    /// Drop the cached chips, so that the next call to `chips` scans sysfs again.
+    ///
+    /// The clones of the context made before are not affected.
+    pub fn invalidate(&mut self) {
+        self.chips.take();
+    }
+
+    fn chips_with<F>(&self, scan: F) -> Result<&[Chip], Error>
+    where
+        F: FnOnce(&Context) -> Result<Vec<Chip>, Error>,
+    {
+        if self.chips.get().is_none() {
+            let chips = Rc::new(scan(self)?);
+            let _ = self.chips.set(chips);
+        }
+        Ok(self
+            .chips
+            .get()
+            .map(|chips| chips.as_slice())
+            .unwrap_or_default())
+    }
+
+    /// Parse the configuration files again and replace the current configuration.
+    ///
+    /// All the chips, features and subfeatures built from this context, or one of
+    /// its clones, use the new configuration without being rebuilt. On error, the
+    /// current configuration is kept.
+    #[cfg(feature = "sensorsconf")]
+    pub fn reload_config(&mut self) -> Result<(), Error> {
+        let file = self.config.borrow().file.clone();
+        let config = Config::load(file.as_deref())?;
+        *self.config.borrow_mut() = config;
+        Ok(())
+    }
+
+    pub(crate) fn sysfs_root(&self) -> &Path {
+        self.sysfs_root.as_ref()
+    }
+
+    pub(crate) fn fs(&self) -> &Fs {
+        &self.fs
     }
 
     pub(crate) fn adapters(&self) -> &Vec<BusAdapter> {
         self.adapters.as_ref()
     }
+
+    #[cfg(feature = "sensorsconf")]
+    pub(crate) fn config(&self) -> Ref<'_, CfgFile> {
+        Ref::map(self.config.borrow(), |config| &config.cfg)
+    }
+
+    /// A context without any bus adapter, for unit tests.
+    #[cfg(test)]
+    #[allow(dead_code)]
+    pub(crate) fn empty() -> Context {
+        Context {
+            sysfs_root: Rc::from(Path::new(SYSFS_MOUNT)),
+            fs: StdFs::shared(),
+            adapters: Rc::new(Vec::new()),
+            chips: OnceCell::new(),
+            #[cfg(feature = "sensorsconf")]
+            config: Default::default(),
+        }
+    }
+
+    /// A context without any bus adapter, using the given configuration file.
+    #[cfg(all(test, feature = "sensorsconf"))]
+    pub(crate) fn with_config(file: &Path) -> Result<Context, Error> {
+        Ok(Context {
+            sysfs_root: Rc::from(Path::new(SYSFS_MOUNT)),
+            fs: StdFs::shared(),
+            adapters: Rc::new(Vec::new()),
+            chips: OnceCell::new(),
+            config: Rc::new(RefCell::new(Config::load(Some(file))?)),
+        })
+    }
+}
+
+/// Link between a feature, or one of its subfeatures, and the configuration of its chip.
+#[cfg(feature = "sensorsconf")]
+#[derive(Clone, Debug)]
+pub(crate) struct FeatureConfig {
+    context: Context,
+    chip_name: Rc<str>,
+    feature_name: Rc<str>,
+}
+
+#[cfg(feature = "sensorsconf")]
+impl FeatureConfig {
+    pub(crate) fn new(context: &Context, chip_name: Rc<str>, feature_name: &str) -> FeatureConfig {
+        FeatureConfig {
+            context: context.clone(),
+            chip_name,
+            feature_name: Rc::from(feature_name),
+        }
+    }
+
+    /// Return the label set by the configuration, if any.
+    pub(crate) fn label(&self) -> Option<String> {
+        self.context
+            .config()
+            .label(&self.chip_name, &self.feature_name)
+            .map(String::from)
+    }
+
+    /// Apply the configured compute statement to a value read from sysfs.
+    /// Return the value unchanged when there is no compute statement.
+    pub(crate) fn compute_from(&self, value: f64) -> Result<f64, Error> {
+        match self
+            .context
+            .config()
+            .compute(&self.chip_name, &self.feature_name)
+        {
+            Some(compute) => compute.eval_from_proc(value),
+            None => Ok(value),
+        }
+    }
+
+    /// Return the configured compute statement, if any.
+    pub(crate) fn compute_statement(&self) -> Option<String> {
+        self.context
+            .config()
+            .compute(&self.chip_name, &self.feature_name)
+            .map(StmtCompute::statement)
+    }
+
+    /// Return the expression of the configured compute statement converting the values
+    /// read from sysfs, if any.
+    pub(crate) fn compute_expr(&self) -> Option<Expr> {
+        self.context
+            .config()
+            .compute(&self.chip_name, &self.feature_name)
+            .map(|compute| compute.expr_from_proc().clone())
+    }
+
+    /// Apply the configured reverse compute statement to a value to be written to sysfs.
+    /// Return the value unchanged when there is no compute statement.
+    pub(crate) fn compute_to(&self, value: f64) -> Result<f64, Error> {
+        match self
+            .context
+            .config()
+            .compute(&self.chip_name, &self.feature_name)
+        {
+            Some(compute) => compute.eval_to_proc(value),
+            None => Ok(value),
+        }
+    }
+}
+
+/// Run `scan` for `Context::watch_chips`, again while a file vanishes during the scan.
+///
+/// Return `None` if the file is still missing after `WATCH_RETRIES` retries.
+fn scan_retrying<F>(mut scan: F) -> Result<Option<Vec<Chip>>, Error>
+where
+    F: FnMut() -> Result<Vec<Chip>, Error>,
+{
+    let mut delay = WATCH_RETRY_DELAY;
+    for _ in 0..WATCH_RETRIES {
+        match scan() {
+            Err(Error::IoAt { source, .. }) if source.kind() == io::ErrorKind::NotFound => {
+                thread::sleep(delay);
+                delay *= 2;
+            }
+            result => return scan_result(result),
+        }
+    }
+    match scan() {
+        Err(Error::IoAt { path, source }) if source.kind() == io::ErrorKind::NotFound => {
+            log::warn!("{} vanished during the scan", path.display());
+            Ok(None)
+        }
+        result => scan_result(result),
+    }
+}
+
+fn scan_result(result: Result<Vec<Chip>, Error>) -> Result<Option<Vec<Chip>>, Error> {
+    match result {
+        Err(Error::Io(err)) if err.kind() == io::ErrorKind::NotFound => {
+            log::debug!("No hwmon class: {}", err);
+            Ok(Some(Vec::new()))
+        }
+        result => result.map(Some),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::feature::FeatureType;
+    #[cfg(feature = "sensorsconf")]
+    use crate::testutil::fake_chip_with_context;
+    use crate::testutil::TempDir;
+
+    #[test]
+    fn context_chips_cache() {
+        let dir = TempDir::new();
+        dir.file("name", "dummy");
+        dir.file("temp1_input", "45000");
+
+        let scans = Cell::new(0);
+        let scan = |context: &Context| {
+            scans.set(scans.get() + 1);
+            Ok(vec![Chip::from_path(dir.path(), None, context).unwrap()])
+        };
+
+        let mut context = Context::empty();
+        let first = context.chips_with(scan).unwrap().as_ptr();
+        let second = context.chips_with(scan).unwrap().as_ptr();
+        assert_eq!(first, second);
+        assert_eq!(scans.get(), 1);
+
+        let clone = context.clone();
+        assert_eq!(clone.chips_with(scan).unwrap().as_ptr(), first);
+        assert_eq!(scans.get(), 1);
+
+        context.invalidate();
+        assert_eq!(context.chips_with(scan).unwrap().len(), 1);
+        assert_eq!(scans.get(), 2);
+    }
+
+    #[test]
+    fn context_sysfs_unavailable() {
+        let dir = TempDir::new();
+        dir.file("kernel/.keep", "");
+
+        match Context::from_sysfs_root(dir.path(), None) {
+            Err(Error::SysfsUnavailable(path)) => assert_eq!(path, dir.path()),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn context_scan_fixture_tree() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/sysfs");
+        let context = Context::from_sysfs_root(&root, None).unwrap();
+        assert_eq!(context.sysfs_root(), root);
+
+        let chips = context.chips().unwrap();
+        let mut names: Vec<String> = chips.iter().map(Chip::name).collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec!["acpitz-virtual-0", "it87-isa-0290", "lm75-i2c-1-48"]
+        );
+
+        let lm75 = chips.iter().find(|chip| chip.prefix() == "lm75").unwrap();
+        assert_eq!(
+            lm75.bus().adapter_name().as_deref(),
+            Some("SMBus I801 adapter at f040")
+        );
+        let temp = lm75.feature(FeatureType::Temperature, 1).unwrap();
+        assert_eq!(temp.input_value(), Some(45.5));
+    }
+
+    #[test]
+    fn context_from_sysfs_root_fixture() {
+        let root = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/sysfs");
+        let context = Context::from_sysfs_root(root, None).unwrap();
+
+        let chips = context.chips().unwrap();
+        let it87 = chips.iter().find(|chip| chip.prefix() == "it87").unwrap();
+        assert_eq!(it87.bus().get_type(), crate::BusType::ISA);
+        assert_eq!(it87.address(), 0x290);
+        let fan = it87.feature(FeatureType::Fan, 1).unwrap();
+        assert_eq!(fan.input_value(), Some(1500.0));
+        let volt = it87.feature(FeatureType::Voltage, 0).unwrap();
+        assert_eq!(volt.input_value(), Some(1.2));
+    }
+
+    #[cfg(feature = "sensorsconf")]
+    #[test]
+    fn context_config_bus_adapter_name() {
+        let dir = TempDir::new();
+        let conf = dir.file("sensors.conf", "bus \"i2c-1\" \"Main SMBus\"\n");
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/sysfs");
+        let context = Context::from_sysfs_root(&root, Some(&conf)).unwrap();
+
+        let chips = context.chips().unwrap();
+        let lm75 = chips.iter().find(|chip| chip.prefix() == "lm75").unwrap();
+        assert_eq!(lm75.bus().adapter_name().as_deref(), Some("Main SMBus"));
+        let acpitz = chips.iter().find(|chip| chip.prefix() == "acpitz").unwrap();
+        assert_eq!(
+            acpitz.bus().adapter_name().as_deref(),
+            Some("Virtual device")
+        );
+    }
+
+    #[cfg(feature = "sensorsconf")]
+    #[test]
+    fn context_reload_config_chip_adapter_name() {
+        let dir = TempDir::new();
+        let conf = dir.file("sensors.conf", "");
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/sysfs");
+        let mut context = Context::from_sysfs_root(&root, Some(&conf)).unwrap();
+
+        let chips = chip::read_sysfs_chips(&context).unwrap();
+        let lm75 = chips.iter().find(|chip| chip.prefix() == "lm75").unwrap();
+        assert_eq!(
+            lm75.adapter_name().as_deref(),
+            Some("SMBus I801 adapter at f040")
+        );
+
+        dir.file("sensors.conf", "bus \"i2c-1\" \"Main SMBus\"\n");
+        context.reload_config().unwrap();
+        assert_eq!(lm75.adapter_name().as_deref(), Some("Main SMBus"));
+    }
+
+    #[cfg(feature = "sensorsconf")]
+    #[test]
+    fn context_reload_config_label() {
+        let dir = TempDir::new();
+        let conf = dir.file(
+            "sensors.conf",
+            "chip \"dummy-*\"\n    label temp1 \"CPU\"\n",
+        );
+
+        let mut context = Context::with_config(&conf).unwrap();
+        let (_chip_dir, chip) =
+            fake_chip_with_context(&context, "dummy", &[("temp1_input", "45000")]);
+        let feature = chip.feature(FeatureType::Temperature, 1).unwrap();
+        assert_eq!(feature.label(), "CPU");
+
+        dir.file(
+            "sensors.conf",
+            "chip \"dummy-*\"\n    label temp1 \"Board\"\n",
+        );
+        context.reload_config().unwrap();
+        assert_eq!(feature.label(), "Board");
+    }
+
+    #[cfg(feature = "sensorsconf")]
+    #[test]
+    fn context_reload_config_compute() {
+        let dir = TempDir::new();
+        let conf = dir.file(
+            "sensors.conf",
+            "chip \"dummy-*\"\n    label temp1 \"CPU\"\n",
+        );
+
+        let mut context = Context::with_config(&conf).unwrap();
+        let (_chip_dir, chip) =
+            fake_chip_with_context(&context, "dummy", &[("temp1_input", "45000")]);
+        let feature = chip.feature(FeatureType::Temperature, 1).unwrap();
+        assert_eq!(feature.input_value(), Some(45.0));
+
+        dir.file(
+            "sensors.conf",
+            "chip \"dummy-*\"\n    compute temp1 @*2, @/2\n",
+        );
+        context.reload_config().unwrap();
+        assert_eq!(feature.input_value(), Some(90.0));
+        assert_eq!(feature.label(), "temp1");
+    }
+
+    #[test]
+    fn context_chip_by_name() {
+        let mut mock = MockFs::new();
+        mock.dir("/sys/class/i2c-adapter");
+        mock.file("/sys/class/hwmon/hwmon0/name", "acpitz");
+        mock.file("/sys/class/hwmon/hwmon0/temp1_input", "27800");
+        mock.file("/sys/class/hwmon/hwmon1/name", "dummy");
+        mock.file("/sys/class/hwmon/hwmon1/temp1_input", "45000");
+        let context = Context::with_mock(mock).unwrap();
+
+        let chip = context.chip_by_name("dummy-virtual-0").unwrap().unwrap();
+        assert_eq!(chip.prefix(), "dummy");
+        assert_eq!(chip.path(), Path::new("/sys/class/hwmon/hwmon1"));
+        assert!(context.chip_by_name("dummy").unwrap().is_none());
+        assert!(context.chip_by_name("lm75-i2c-1-48").unwrap().is_none());
+    }
+
+    #[test]
+    fn context_watch_chips_hotplug() {
+        let mut mock = MockFs::new();
+        mock.dir("/sys/class/i2c-adapter");
+        let context = Context::with_mock(mock.clone()).unwrap();
+
+        let mut scans = Vec::new();
+        context
+            .watch_chips(Duration::ZERO, |chips| {
+                scans.push(chips.iter().map(Chip::name).collect::<Vec<_>>());
+                mock.file("/sys/class/hwmon/hwmon0/name", "dummy");
+                mock.file("/sys/class/hwmon/hwmon0/temp1_input", "45000");
+                if scans.len() == 2 {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            })
+            .unwrap();
+
+        assert_eq!(scans, [vec![], vec![String::from("dummy-virtual-0")]]);
+    }
+
+    #[test]
+    fn scan_retrying_vanished_file() {
+        let vanished = || Error::IoAt {
+            path: PathBuf::from("/sys/class/hwmon/hwmon0/temp1_input"),
+            source: io::ErrorKind::NotFound.into(),
+        };
+
+        let mut scans = 0;
+        let chips = scan_retrying(|| {
+            scans += 1;
+            if scans < 3 {
+                Err(vanished())
+            } else {
+                Ok(Vec::new())
+            }
+        });
+        assert!(matches!(chips, Ok(Some(chips)) if chips.is_empty()));
+        assert_eq!(scans, 3);
+
+        scans = 0;
+        let chips = scan_retrying(|| {
+            scans += 1;
+            Err(vanished())
+        });
+        assert!(matches!(chips, Ok(None)));
+        assert_eq!(scans, WATCH_RETRIES + 1);
+
+        let chips = scan_retrying(|| Err(Error::Io(io::ErrorKind::NotFound.into())));
+        assert!(matches!(chips, Ok(Some(chips)) if chips.is_empty()));
+        let chips = scan_retrying(|| Err(Error::Io(io::ErrorKind::PermissionDenied.into())));
+        assert!(matches!(chips, Err(Error::Io(_))));
+    }
+
+    #[cfg(feature = "sensorsconf")]
+    #[test]
+    fn context_config_non_invertible_compute_warning() {
+        let dir = TempDir::new();
+        let conf = dir.file(
+            "sensors.conf",
+            "chip \"dummy-*\"\n    compute in0 @*2, @/2\n    compute in1 @*2, @*2\n",
+        );
+        let config = Config::load(Some(&conf)).unwrap();
+
+        assert_eq!(
+            non_invertible_computes(&config.cfg),
+            ["Compute statement of in1 for chip dummy-* is not invertible, writes will be wrong"]
+        );
+    }
 }
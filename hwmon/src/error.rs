@@ -5,22 +5,48 @@ use std::error;
 use std::fmt;
 use std::io;
 use std::num;
+use std::path::PathBuf;
 
 use crate::bus::BusType;
 
 #[derive(Debug)]
 pub enum Error {
     Access(&'static str),
+    /// A chip could not be read, like a device on a bus which can not be parsed.
+    Chip(Box<dyn error::Error + Send + Sync>),
+    /// A feature or one of its subfeatures could not be read.
+    Feature(Box<dyn error::Error + Send + Sync>),
+    /// The sensor tree could not be written to an export format.
+    Format(Box<dyn error::Error + Send + Sync>),
+    InvalidValue,
     Io(io::Error),
+    /// An IO error on the given sysfs file.
+    IoAt {
+        path: PathBuf,
+        source: io::Error,
+    },
+    /// The value is outside of the range accepted by the subfeature.
+    OutOfRange {
+        value: f64,
+        min: f64,
+        max: f64,
+    },
     ParseFloat(num::ParseFloatError),
     ParseInt(num::ParseIntError),
     ParseBusName(BusType),
+    ParseConfig(String),
+    SubfeatureType,
+    SysfsUnavailable(PathBuf),
 }
 
 impl error::Error for Error {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match *self {
+            Error::Chip(ref err) => Some(err.as_ref()),
+            Error::Feature(ref err) => Some(err.as_ref()),
+            Error::Format(ref err) => Some(err.as_ref()),
             Error::Io(ref err) => Some(err),
+            Error::IoAt { ref source, .. } => Some(source),
             Error::ParseFloat(ref err) => Some(err),
             Error::ParseInt(ref err) => Some(err),
             _ => None,
@@ -32,10 +58,28 @@ impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             Error::Access(ref err) => write!(f, "Access error: {}", err),
+            Error::Chip(ref err) => write!(f, "Chip error: {}", err),
+            Error::Feature(ref err) => write!(f, "Feature error: {}", err),
+            Error::Format(ref err) => write!(f, "Format error: {}", err),
+            Error::InvalidValue => write!(f, "Invalid value"),
             Error::Io(ref err) => write!(f, "IO error: {}", err),
+            Error::IoAt {
+                ref path,
+                ref source,
+            } => write!(f, "IO error on {}: {}", path.display(), source),
+            Error::OutOfRange { value, min, max } => {
+                write!(f, "Value {} out of range [{}, {}]", value, min, max)
+            }
             Error::ParseFloat(ref err) => write!(f, "ParseFloat error: {}", err),
             Error::ParseInt(ref err) => write!(f, "ParseInt error: {}", err),
             Error::ParseBusName(ref bus) => write!(f, "Failed to parse {} bus name", bus),
+            Error::ParseConfig(ref err) => write!(f, "Failed to parse configuration: {}", err),
+            Error::SubfeatureType => write!(f, "Operation not supported by this subfeature type"),
+            Error::SysfsUnavailable(ref path) => write!(
+                f,
+                "sysfs is not available at {}, it is probably not mounted",
+                path.display()
+            ),
         }
     }
 }
@@ -58,6 +102,31 @@ impl From<num::ParseIntError> for Error {
     }
 }
 
+#[cfg(feature = "serde")]
+impl From<toml::ser::Error> for Error {
+    fn from(err: toml::ser::Error) -> Error {
+        Error::Format(Box::new(err))
+    }
+}
+
+impl From<ChipError> for Error {
+    fn from(err: ChipError) -> Error {
+        Error::Chip(Box::new(err))
+    }
+}
+
+impl From<FeatureError> for Error {
+    fn from(err: FeatureError) -> Error {
+        Error::Feature(Box::new(err))
+    }
+}
+
+impl From<SubfeatureError> for Error {
+    fn from(err: SubfeatureError) -> Error {
+        Error::Feature(Box::new(err))
+    }
+}
+
 #[derive(Debug)]
 pub(crate) enum ChipError {
     Io(io::Error),
@@ -1,12 +1,17 @@
 // SPDX-FileCopyrightText: 2018 Camille019
 // SPDX-License-Identifier: MPL-2.0
 
+use std::collections::BTreeMap;
+use std::fmt;
 use std::io;
 use std::path::{Path, PathBuf};
 use std::slice;
+use std::str::FromStr;
 
+#[cfg(feature = "sensorsconf")]
+use crate::context::FeatureConfig;
 use crate::error::*;
-use crate::subfeature::{Subfeature, SubfeatureType};
+use crate::subfeature::*;
 use crate::sysfs;
 
 #[derive(Clone, Copy, Debug, Hash, Eq, PartialEq, Ord, PartialOrd)]
@@ -24,6 +29,72 @@ pub enum FeatureType {
     BeepEnable,
 }
 
+impl FeatureType {
+    /// Return every subfeature type which can belong to a feature of this type.
+    pub fn subfeature_types(self) -> &'static [SubfeatureType] {
+        match self {
+            FeatureType::Fan => Fan::SUBFEATURE_TYPES,
+            FeatureType::Pwm => Pwm::SUBFEATURE_TYPES,
+            FeatureType::Temperature => Temperature::SUBFEATURE_TYPES,
+            FeatureType::Voltage => Voltage::SUBFEATURE_TYPES,
+            FeatureType::Current => Current::SUBFEATURE_TYPES,
+            FeatureType::Power => Power::SUBFEATURE_TYPES,
+            FeatureType::Energy => Energy::SUBFEATURE_TYPES,
+            FeatureType::Humidity => Humidity::SUBFEATURE_TYPES,
+            FeatureType::Cpu => &[SubfeatureType::Cpu],
+            FeatureType::Intrusion => Intrusion::SUBFEATURE_TYPES,
+            FeatureType::BeepEnable => &[SubfeatureType::BeepEnable],
+        }
+    }
+
+    /// Return the sysfs attribute prefix of the feature type, like `temp` or `in`, the
+    /// inverse of the `FromStr` implementation.
+    pub fn sysfs_prefix(self) -> &'static str {
+        match self {
+            FeatureType::Fan => "fan",
+            FeatureType::Pwm => "pwm",
+            FeatureType::Temperature => "temp",
+            FeatureType::Voltage => "in",
+            FeatureType::Current => "curr",
+            FeatureType::Power => "power",
+            FeatureType::Energy => "energy",
+            FeatureType::Humidity => "humidity",
+            FeatureType::Cpu => "cpu",
+            FeatureType::Intrusion => "intrusion",
+            FeatureType::BeepEnable => "beep_enable",
+        }
+    }
+}
+
+/// Display the human-readable name of the feature type, like `Temperature`.
+impl fmt::Display for FeatureType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            FeatureType::Fan => "Fan",
+            FeatureType::Pwm => "PWM",
+            FeatureType::Temperature => "Temperature",
+            FeatureType::Voltage => "Voltage",
+            FeatureType::Current => "Current",
+            FeatureType::Power => "Power",
+            FeatureType::Energy => "Energy",
+            FeatureType::Humidity => "Humidity",
+            FeatureType::Cpu => "CPU",
+            FeatureType::Intrusion => "Intrusion",
+            FeatureType::BeepEnable => "Beep enable",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Parse the sysfs attribute prefix of a feature type, like `temp` or `in`.
+impl FromStr for FeatureType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<FeatureType, Error> {
+        feature_type_from_prefix(s).ok_or(Error::InvalidValue)
+    }
+}
+
 impl From<SubfeatureType> for FeatureType {
     fn from(sf_type: SubfeatureType) -> FeatureType {
         match sf_type {
@@ -42,6 +113,20 @@ impl From<SubfeatureType> for FeatureType {
     }
 }
 
+/// Values of the subfeatures of a feature, by subfeature type.
+pub type FeatureReadings = BTreeMap<SubfeatureType, Result<f64, Error>>;
+
+/// State of a temperature sensor, see [`Feature::temperature_status`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TempStatus {
+    /// The temperature in °C.
+    Ok(f64),
+    /// The sensor is present, but the chip reports a fault, like an open diode.
+    Fault,
+    /// The temperature can not be read.
+    Missing,
+}
+
 pub struct SubfeatureIter<'a> {
     inner: slice::Iter<'a, Subfeature>,
 }
@@ -52,8 +137,20 @@ impl<'a> Iterator for SubfeatureIter<'a> {
     fn next(&mut self) -> Option<Self::Item> {
         self.inner.next()
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for SubfeatureIter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
 }
 
+impl ExactSizeIterator for SubfeatureIter<'_> {}
+
 #[derive(Debug)]
 pub struct Feature {
     dir: PathBuf,
@@ -61,6 +158,11 @@ pub struct Feature {
     number: u32,
     feature_type: FeatureType,
     subfeatures: Vec<Subfeature>,
+    /// Label found when the feature was built, see `label_cached`.
+    cached_label: Option<String>,
+    fs: sysfs::Fs,
+    #[cfg(feature = "sensorsconf")]
+    config: Option<FeatureConfig>,
 }
 
 impl Feature {
@@ -82,7 +184,10 @@ impl Feature {
     /// Look up the label of the feature in config files or in sysfs.
     /// If no label exists for this feature, its name is returned itself.
     pub fn label(&self) -> String {
-        // TODO check user specified label
+        #[cfg(feature = "sensorsconf")]
+        if let Some(label) = self.config.as_ref().and_then(FeatureConfig::label) {
+            return label;
+        }
 
         if let Ok(label) = self.read_sysfs_label() {
             label
@@ -91,6 +196,14 @@ impl Feature {
         }
     }
 
+    /// Return the label found in config files or in sysfs when the feature was built,
+    /// without any IO. Unlike `label`, it does not follow `Context::reload_config`.
+    ///
+    /// Return `None` if the feature had no label.
+    pub fn label_cached(&self) -> Option<&str> {
+        self.cached_label.as_deref()
+    }
+
     /// Return the subfeature of the given type, if it exists, `None` otherwise.
     pub fn subfeature(&self, subfeature_type: SubfeatureType) -> Option<&Subfeature> {
         self.subfeatures
@@ -98,41 +211,556 @@ impl Feature {
             .find(|&subfeature| subfeature.get_type() == subfeature_type)
     }
 
+    /// Read the main value of the feature, like the temperature of a temperature
+    /// feature or the duty cycle of a pwm feature.
+    ///
+    /// Power features fall back to the average power when there is no instantaneous
+    /// power. `Cpu` features return the VID voltage. `Intrusion` and `BeepEnable`
+    /// are flags rather than measurements and always return `None`.
+    ///
+    /// Return `None` as well if the subfeature does not exist or can not be read.
+    pub fn input_value(&self) -> Option<f64> {
+        self.input_subfeature().and_then(|sf| sf.read_value().ok())
+    }
+
+    /// Read the value of every readable subfeature of the feature, in display order:
+    /// the inputs first, then the limits, the other values, and the alarms last. Within
+    /// each group the subfeatures follow the declaration order of their types.
+    ///
+    /// Unlike `read_all_values`, the subfeatures which can not be read are skipped.
+    pub fn read_ordered(&self) -> Vec<(SubfeatureType, f64)> {
+        let mut values: Vec<(SubfeatureType, f64)> = self
+            .subfeatures
+            .iter()
+            .filter(|subfeature| subfeature.is_readable())
+            .filter_map(|subfeature| Some((subfeature.get_type(), subfeature.read_value().ok()?)))
+            .collect();
+        values.sort_by_key(|(sf_type, _)| {
+            (
+                !sf_type.is_input(),
+                !sf_type.is_limit(),
+                sf_type.is_alarm(),
+                *sf_type,
+            )
+        });
+        values
+    }
+
+    /// Read the state of a temperature feature.
+    ///
+    /// A sensor whose `fault` subfeature is raised is reported as `TempStatus::Fault`,
+    /// even if its input can be read. A sensor whose input is missing or can not be
+    /// read is reported as `TempStatus::Missing`, as are other feature types.
+    pub fn temperature_status(&self) -> TempStatus {
+        if self.feature_type != FeatureType::Temperature {
+            return TempStatus::Missing;
+        }
+
+        let fault = self
+            .subfeature(SubfeatureType::Temperature(Temperature::Fault))
+            .and_then(|fault| fault.read_value().ok());
+        if fault.is_some_and(|fault| fault != 0.0) {
+            return TempStatus::Fault;
+        }
+
+        match self
+            .subfeature(SubfeatureType::Temperature(Temperature::Input))
+            .map(Subfeature::read_value)
+        {
+            Some(Ok(value)) => TempStatus::Ok(value),
+            _ => TempStatus::Missing,
+        }
+    }
+
+    /// Read the `fault` subfeature of a fan, temperature or humidity feature, `true`
+    /// when the sensor is reported broken or disconnected.
+    ///
+    /// Return `None` if the feature has no such subfeature or it can not be read.
+    pub fn fault(&self) -> Option<bool> {
+        let sf_type = match self.feature_type {
+            FeatureType::Fan => SubfeatureType::Fan(Fan::Fault),
+            FeatureType::Temperature => SubfeatureType::Temperature(Temperature::Fault),
+            FeatureType::Humidity => SubfeatureType::Humidity(Humidity::Fault),
+            _ => return None,
+        };
+        let value = self.subfeature(sf_type)?.read_value().ok()?;
+        Some(value != 0.0)
+    }
+
+    /// Read the value of every subfeature of the feature.
+    ///
+    /// A subfeature which can not be read is recorded with its error, it does not
+    /// prevent the other ones from being read.
+    pub fn read_all_values(&self) -> FeatureReadings {
+        self.subfeatures
+            .iter()
+            .map(|subfeature| (subfeature.get_type(), subfeature.read_value()))
+            .collect()
+    }
+
+    /// Return the types of the alarm subfeatures currently raised.
+    ///
+    /// Alarms which can not be read are not listed.
+    pub fn active_alarms(&self) -> Vec<SubfeatureType> {
+        self.subfeatures
+            .iter()
+            .filter(|subfeature| subfeature.is_alarm_active().unwrap_or(false))
+            .map(Subfeature::get_type)
+            .collect()
+    }
+
+    /// Read a limit of the feature along with its hysteresis, if the limit has one
+    /// and it exists, e.g. `Temperature::Max` with `Temperature::Max_Hyst`.
+    ///
+    /// Return `None` if the limit does not exist or can not be read.
+    pub fn limit_with_hyst(&self, limit: SubfeatureType) -> Option<(f64, Option<f64>)> {
+        let value = self.subfeature(limit)?.read_value().ok()?;
+        let hyst = limit
+            .hysteresis()
+            .and_then(|hyst| self.subfeature(hyst))
+            .and_then(|sf| sf.read_value().ok());
+        Some((value, hyst))
+    }
+
+    /// An iterator visiting the readable limits of the feature in display order, the
+    /// declaration order of the subfeature types, with their value and the value of
+    /// their hysteresis, if any. See `SubfeatureType::is_limit`.
+    ///
+    /// The hysteresis subfeatures come with their limit rather than on their own.
+    pub fn iter_limits(&self) -> impl Iterator<Item = (SubfeatureType, f64, Option<f64>)> + '_ {
+        let sf_types = self.feature_type.subfeature_types();
+        sf_types
+            .iter()
+            .copied()
+            .filter(|sf_type| sf_type.is_limit())
+            .filter(move |sf_type| !sf_types.iter().any(|l| l.hysteresis() == Some(*sf_type)))
+            .filter_map(move |limit| {
+                let (value, hyst) = self.limit_with_hyst(limit)?;
+                Some((limit, value, hyst))
+            })
+    }
+
+    /// Write the hysteresis of a limit, e.g. `Temperature::Max_Hyst` for
+    /// `Temperature::Max`, as an absolute temperature in °C.
+    ///
+    /// The sysfs interface always uses absolute values, but many drivers store the
+    /// hysteresis as a delta below the limit: they may round it, clamp it to a
+    /// maximal delta, or move it when the limit changes. Some chips also share one
+    /// hysteresis between several limits. Read the value back to know what was kept.
+    ///
+    /// Return `Error::SubfeatureType` if the limit has no hysteresis or the feature
+    /// does not have it, and `Error::Access` if it is not writable.
+    pub fn write_hyst(&self, limit: SubfeatureType, hyst_celsius: f64) -> Result<(), Error> {
+        limit
+            .hysteresis()
+            .and_then(|hyst| self.subfeature(hyst))
+            .ok_or(Error::SubfeatureType)?
+            .write_value(hyst_celsius)
+    }
+
+    /// Read the speed of a fan feature, in RPM.
+    ///
+    /// The hwmon sysfs interface requires `fanN_input` to be in RPM, so the driver
+    /// already accounts for `fanN_div` and `fanN_pulses`. On the old chips counting
+    /// tachometer periods, the speed is `1350000 / (count * div)`, and `pulses` is
+    /// the number of tachometer pulses per revolution. Both attributes only tell the
+    /// driver how to compute the speed, changing them changes what it reports, so
+    /// they are not applied again here. This has been the case since the hwmon class
+    /// was introduced in Linux 2.6.14.
+    ///
+    /// Return `None` for other feature types, or if the speed can not be read.
+    pub fn fan_rpm(&self) -> Option<f64> {
+        self.subfeature(SubfeatureType::Fan(Fan::Input))?
+            .read_value()
+            .ok()
+    }
+
+    /// Read the target speed of a fan feature, in RPM, for fans in closed-loop mode.
+    ///
+    /// Return `Error::SubfeatureType` if the feature has no `target` subfeature.
+    pub fn read_fan_target(&self) -> Result<f64, Error> {
+        self.subfeature(SubfeatureType::Fan(Fan::Target))
+            .ok_or(Error::SubfeatureType)?
+            .read_value()
+    }
+
+    /// Write the target speed of a fan feature, in RPM.
+    ///
+    /// Return `Error::SubfeatureType` if the feature has no `target` subfeature, and
+    /// `Error::Access` if it is not writable.
+    pub fn write_fan_target(&self, rpm: f64) -> Result<(), Error> {
+        self.subfeature(SubfeatureType::Fan(Fan::Target))
+            .ok_or(Error::SubfeatureType)?
+            .write_value(rpm)
+    }
+
+    /// Read the power cap of a power feature, in W.
+    ///
+    /// Return `Error::SubfeatureType` if the feature has no `cap` subfeature.
+    pub fn read_power_cap(&self) -> Result<f64, Error> {
+        self.subfeature(SubfeatureType::Power(Power::Cap))
+            .ok_or(Error::SubfeatureType)?
+            .read_value()
+    }
+
+    /// Write the power cap of a power feature, in W.
+    ///
+    /// The value is checked against the `cap_min` and `cap_max` bounds, when the chip
+    /// provides them. Return `Error::SubfeatureType` if the feature has no `cap`
+    /// subfeature, `Error::OutOfRange` if the value is outside of the bounds, and
+    /// `Error::Access` if it is not writable.
+    pub fn write_power_cap(&self, watts: f64) -> Result<(), Error> {
+        let cap = self
+            .subfeature(SubfeatureType::Power(Power::Cap))
+            .ok_or(Error::SubfeatureType)?;
+        if !cap.is_writable() {
+            return Err(Error::Access("Subfeature not writable"));
+        }
+
+        let bound = |sf_type| {
+            self.subfeature(sf_type)
+                .and_then(|subfeature| subfeature.read_value().ok())
+        };
+        let min = bound(SubfeatureType::Power(Power::Cap_Min)).unwrap_or(f64::NEG_INFINITY);
+        let max = bound(SubfeatureType::Power(Power::Cap_Max)).unwrap_or(f64::INFINITY);
+        if !(min..=max).contains(&watts) {
+            return Err(Error::OutOfRange {
+                value: watts,
+                min,
+                max,
+            });
+        }
+        cap.write_value(watts)
+    }
+
+    /// Return the bounds of the power cap of a power feature, as (`cap_min`, `cap_max`)
+    /// in W.
+    ///
+    /// Return `None` if either bound is missing or can not be read.
+    pub fn power_cap_range(&self) -> Option<(f64, f64)> {
+        let min = self.subfeature(SubfeatureType::Power(Power::Cap_Min))?;
+        let max = self.subfeature(SubfeatureType::Power(Power::Cap_Max))?;
+        Some((min.read_value().ok()?, max.read_value().ok()?))
+    }
+
+    /// Read the trip points of a pwm feature, from the `pwmN_auto_pointM_temp` and
+    /// `pwmN_auto_pointM_pwm` attribute pairs, as (temperature in °C, raw duty cycle).
+    ///
+    /// The points are found by scanning the chip directory and are returned by
+    /// increasing point number. Points missing one of the attributes, or which can not
+    /// be read, are skipped. Return an empty list for other feature types.
+    pub fn auto_points(&self) -> Vec<(f64, f64)> {
+        if self.feature_type != FeatureType::Pwm {
+            return Vec::new();
+        }
+
+        let prefix = format!("{}_auto_point", self.name);
+        let mut numbers: Vec<u32> = match self.fs.read_dir(&self.dir) {
+            Ok(entries) => entries
+                .iter()
+                .filter_map(|path| {
+                    path.file_name()?
+                        .to_str()?
+                        .strip_prefix(prefix.as_str())?
+                        .strip_suffix("_temp")?
+                        .parse()
+                        .ok()
+                })
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        numbers.sort_unstable();
+
+        let temp_type = SubfeatureType::Temperature(Temperature::Input);
+        let read = |attr: String| {
+            self.fs
+                .read_attr(&self.dir, &attr)
+                .ok()
+                .and_then(|value| value.parse::<f64>().ok())
+        };
+        numbers
+            .into_iter()
+            .filter_map(|point| {
+                let temp = read(format!("{}{}_temp", prefix, point))?;
+                let pwm = read(format!("{}{}_pwm", prefix, point))?;
+                Some((temp_type.to_unity(temp), pwm))
+            })
+            .collect()
+    }
+
+    /// Write the trip point `point` of a pwm feature, with the temperature in °C and
+    /// the raw duty cycle, from 0 to 255.
+    ///
+    /// Return `Error::SubfeatureType` if the feature has no such point, `Error::Access`
+    /// if one of its attributes is not writable, and `Error::InvalidValue` if a value is
+    /// out of range. Both values are checked before any of them is written.
+    pub fn write_auto_point(&self, point: u32, temp: f64, pwm: f64) -> Result<(), Error> {
+        let prefix = format!("{}_auto_point{}", self.name, point);
+        let temp_path = self.dir.join(format!("{}_temp", prefix));
+        let pwm_path = self.dir.join(format!("{}_pwm", prefix));
+        if self.feature_type != FeatureType::Pwm
+            || !self.fs.is_file(&temp_path)
+            || !self.fs.is_file(&pwm_path)
+        {
+            return Err(Error::SubfeatureType);
+        }
+        let io_error = |path: &Path| {
+            let path = path.to_owned();
+            move |source| Error::IoAt { path, source }
+        };
+        for path in [&temp_path, &pwm_path] {
+            let (_, writable) = read_permissions(&*self.fs, path).map_err(io_error(path))?;
+            if !writable {
+                return Err(Error::Access("Auto point not writable"));
+            }
+        }
+
+        let temp = SubfeatureType::Temperature(Temperature::Input)
+            .to_native(temp)
+            .ok_or(Error::InvalidValue)?;
+        if !(0.0..=255.0).contains(&pwm) {
+            return Err(Error::InvalidValue);
+        }
+        let pwm = SubfeatureType::Pwm(Pwm::Pwm)
+            .to_native(pwm)
+            .ok_or(Error::InvalidValue)?;
+
+        for (path, value) in [(&temp_path, temp), (&pwm_path, pwm)] {
+            self.fs
+                .write_file(path, &value.to_string())
+                .map_err(io_error(path))?;
+        }
+        Ok(())
+    }
+
+    /// Read the kind of thermal sensor of a temperature feature.
+    ///
+    /// Return `Error::SubfeatureType` if the feature has no `type` subfeature.
+    pub fn read_temp_type(&self) -> Result<TempSensorKind, Error> {
+        self.subfeature(SubfeatureType::Temperature(Temperature::Type))
+            .ok_or(Error::SubfeatureType)?
+            .read_temp_type()
+    }
+
+    /// Select the kind of thermal sensor of a temperature feature, on chips which
+    /// support several kinds.
+    ///
+    /// Return `Error::SubfeatureType` if the feature has no `type` subfeature,
+    /// `Error::InvalidValue` for `TempSensorKind::Unknown`, which has no sysfs value,
+    /// and `Error::Access` if the subfeature is not writable.
+    pub fn write_temp_type(&self, kind: TempSensorKind) -> Result<(), Error> {
+        self.subfeature(SubfeatureType::Temperature(Temperature::Type))
+            .ok_or(Error::SubfeatureType)?
+            .write_temp_type(kind)
+    }
+
+    /// Enable or disable the audible alarm of the feature, through its `beep` subfeature.
+    ///
+    /// Return `Error::SubfeatureType` if the feature has no `beep` subfeature, and
+    /// `Error::Access` if it is not writable.
+    pub fn set_beep(&self, enabled: bool) -> Result<(), Error> {
+        self.beep_subfeature()
+            .ok_or(Error::SubfeatureType)?
+            .write_value(if enabled { 1.0 } else { 0.0 })
+    }
+
+    /// Return whether the audible alarm of a limit of the feature, like
+    /// `Temperature::Crit_Max`, is enabled.
+    ///
+    /// The beep subfeature of the limit is used when the driver provides one, like
+    /// `temp1_crit_beep`, and the `beep` subfeature of the feature otherwise. Return
+    /// `None` if the limit does not belong to the feature, or if there is no beep
+    /// subfeature which can be read.
+    pub fn limit_beep_enabled(&self, limit: SubfeatureType) -> Option<bool> {
+        if limit.feature_type() != self.feature_type {
+            return None;
+        }
+
+        let beep = limit
+            .beep()
+            .and_then(|sf_type| self.subfeature(sf_type))
+            .or_else(|| self.beep_subfeature())?;
+        beep.read_value().ok().map(|value| value != 0.0)
+    }
+
+    fn beep_subfeature(&self) -> Option<&Subfeature> {
+        let sf_type = match self.feature_type {
+            FeatureType::Fan => SubfeatureType::Fan(Fan::Beep),
+            FeatureType::Temperature => SubfeatureType::Temperature(Temperature::Beep),
+            FeatureType::Voltage => SubfeatureType::Voltage(Voltage::Beep),
+            FeatureType::Current => SubfeatureType::Current(Current::Beep),
+            FeatureType::Intrusion => SubfeatureType::Intrusion(Intrusion::Beep),
+            _ => return None,
+        };
+        self.subfeature(sf_type)
+    }
+
+    /// Read the critical threshold of a temperature feature, its `crit` subfeature.
+    ///
+    /// Return `None` for other feature types, or if the threshold can not be read.
+    pub fn crit(&self) -> Option<f64> {
+        self.temperature_value(Temperature::Crit_Max)
+    }
+
+    /// Read the emergency threshold of a temperature feature, the one above `crit`
+    /// at which the hardware shuts down, see `crit`.
+    pub fn emergency(&self) -> Option<f64> {
+        self.temperature_value(Temperature::Emergency)
+    }
+
+    fn temperature_value(&self, temperature: Temperature) -> Option<f64> {
+        self.subfeature(SubfeatureType::Temperature(temperature))?
+            .read_value()
+            .ok()
+    }
+
+    /// Read the averaging window of the `average` subfeatures of the feature, in
+    /// seconds.
+    ///
+    /// Only power features have a per-feature `average_interval` in the hwmon sysfs
+    /// ABI. Chips averaging voltages or currents use the chip-wide `update_interval`,
+    /// see `Chip::update_interval`. Return `None` for the other feature types, or if
+    /// the interval can not be read.
+    pub fn average_interval(&self) -> Option<f64> {
+        let sf_type = match self.feature_type {
+            FeatureType::Power => SubfeatureType::Power(Power::Average_Interval),
+            _ => return None,
+        };
+        self.subfeature(sf_type)?.read_value().ok()
+    }
+
+    /// Read the highest value tracked by the chip for the feature since the last
+    /// `reset_history`.
+    ///
+    /// Power features report the peak of the instantaneous power, `input_highest`.
+    /// Return `None` for the feature types without peak tracking, or if the peak can
+    /// not be read.
+    pub fn peak_high(&self) -> Option<f64> {
+        self.peak_subfeature(true)?.read_value().ok()
+    }
+
+    /// Read the lowest value tracked by the chip for the feature since the last
+    /// `reset_history`, see `peak_high`.
+    pub fn peak_low(&self) -> Option<f64> {
+        self.peak_subfeature(false)?.read_value().ok()
+    }
+
+    fn peak_subfeature(&self, highest: bool) -> Option<&Subfeature> {
+        let sf_type = match (self.feature_type, highest) {
+            (FeatureType::Temperature, true) => SubfeatureType::Temperature(Temperature::Highest),
+            (FeatureType::Temperature, false) => SubfeatureType::Temperature(Temperature::Lowest),
+            (FeatureType::Voltage, true) => SubfeatureType::Voltage(Voltage::Highest),
+            (FeatureType::Voltage, false) => SubfeatureType::Voltage(Voltage::Lowest),
+            (FeatureType::Current, true) => SubfeatureType::Current(Current::Highest),
+            (FeatureType::Current, false) => SubfeatureType::Current(Current::Lowest),
+            (FeatureType::Power, true) => SubfeatureType::Power(Power::Input_Highest),
+            (FeatureType::Power, false) => SubfeatureType::Power(Power::Input_Lowest),
+            _ => return None,
+        };
+        self.subfeature(sf_type)
+    }
+
+    /// Clear the lowest and highest values tracked by the chip for the feature, through
+    /// its `reset_history` subfeature.
+    ///
+    /// Return `Error::SubfeatureType` if the feature has no `reset_history` subfeature,
+    /// and `Error::Access` if it is not writable.
+    pub fn reset_history(&self) -> Result<(), Error> {
+        let sf_type = match self.feature_type {
+            FeatureType::Temperature => SubfeatureType::Temperature(Temperature::Reset_History),
+            FeatureType::Voltage => SubfeatureType::Voltage(Voltage::Reset_History),
+            FeatureType::Current => SubfeatureType::Current(Current::Reset_History),
+            FeatureType::Power => SubfeatureType::Power(Power::Reset_History),
+            _ => return Err(Error::SubfeatureType),
+        };
+        self.subfeature(sf_type)
+            .ok_or(Error::SubfeatureType)?
+            .write_value(1.0)
+    }
+
+    /// An iterator visiting the subfeatures which can be written, in arbitrary order.
+    pub fn writable_subfeatures(&self) -> impl Iterator<Item = &Subfeature> {
+        self.subfeatures
+            .iter()
+            .filter(|subfeature| subfeature.is_writable())
+    }
+
     /// An iterator visiting all subfeatures in arbitrary order.
-    pub fn subfeatures_iter(&self) -> SubfeatureIter {
+    pub fn subfeatures_iter(&self) -> SubfeatureIter<'_> {
         SubfeatureIter {
             inner: self.subfeatures.iter(),
         }
     }
 
-    pub(crate) fn new(dir: &Path, feature_type: FeatureType, number: u32) -> Feature {
+    /// Create the feature of the chip directory `dir`, read from the given file system.
+    pub(crate) fn new(
+        fs: &sysfs::Fs,
+        dir: &Path,
+        feature_type: FeatureType,
+        number: u32,
+    ) -> Feature {
         let name = match feature_type {
-            FeatureType::Voltage => format!("in{}", number),
-            FeatureType::Fan => format!("fan{}", number),
-            FeatureType::Pwm => format!("pwm{}", number),
-            FeatureType::Temperature => format!("temp{}", number),
-            FeatureType::Power => format!("power{}", number),
-            FeatureType::Energy => format!("energy{}", number),
-            FeatureType::Current => format!("curr{}", number),
-            FeatureType::Humidity => format!("humidity{}", number),
             FeatureType::Cpu => format!("cpu{}_vid", number),
-            FeatureType::Intrusion => format!("intrusion{}", number),
             FeatureType::BeepEnable => String::from("beep_enable"),
+            _ => format!("{}{}", feature_type.sysfs_prefix(), number),
         };
 
-        Feature {
+        let mut feature = Feature {
             dir: dir.to_owned(),
             name,
             number,
             feature_type,
             subfeatures: Default::default(),
+            cached_label: None,
+            fs: fs.clone(),
+            #[cfg(feature = "sensorsconf")]
+            config: None,
+        };
+        feature.cached_label = feature.read_sysfs_label().ok();
+        feature
+    }
+
+    /// Link the feature, and its subfeatures, to the configuration of its chip.
+    #[cfg(feature = "sensorsconf")]
+    pub(crate) fn set_config(&mut self, config: FeatureConfig) {
+        for subfeature in self.subfeatures.iter_mut() {
+            subfeature.set_config(Some(config.clone()));
         }
+        if let Some(label) = config.label() {
+            self.cached_label = Some(label);
+        }
+        self.config = Some(config);
+    }
+
+    /// Return the subfeature of the given type, mutably.
+    #[cfg(feature = "sensorsconf")]
+    pub(crate) fn subfeature_mut(&mut self, sf_type: SubfeatureType) -> Option<&mut Subfeature> {
+        self.subfeatures
+            .iter_mut()
+            .find(|subfeature| subfeature.get_type() == sf_type)
+    }
+
+    /// Replace the compute statement of the subfeature of the given type, like
+    /// `@*2, @/2`, without rebuilding the chip.
+    ///
+    /// The statement takes precedence over the one of the configuration file. Return
+    /// `Error::SubfeatureType` if the feature has no such subfeature, and
+    /// `Error::ParseConfig` if the statement can not be parsed.
+    #[cfg(feature = "sensorsconf")]
+    pub fn set_compute_statement(
+        &mut self,
+        sf_type: SubfeatureType,
+        statement: &str,
+    ) -> Result<(), Error> {
+        self.subfeature_mut(sf_type)
+            .ok_or(Error::SubfeatureType)?
+            .set_compute_statement(statement)
     }
 
     ///
     /// Return `None` if
     pub(crate) fn push_subfeature(&mut self, subfeature: Subfeature) -> Result<(), FeatureError> {
-        if FeatureType::from(subfeature.get_type()) == self.feature_type {
+        if subfeature.get_type().feature_type() == self.feature_type {
             log::debug!(
                 "Add subfeature '{}' to feature '{}'",
                 subfeature.name(),
@@ -145,8 +773,729 @@ impl Feature {
         }
     }
 
+    /// Return the subfeature holding the main value of the feature.
+    pub(crate) fn input_subfeature(&self) -> Option<&Subfeature> {
+        let sf_type = match self.feature_type {
+            FeatureType::Fan => SubfeatureType::Fan(Fan::Input),
+            FeatureType::Pwm => SubfeatureType::Pwm(Pwm::Pwm),
+            FeatureType::Temperature => SubfeatureType::Temperature(Temperature::Input),
+            FeatureType::Voltage => SubfeatureType::Voltage(Voltage::Input),
+            FeatureType::Current => SubfeatureType::Current(Current::Input),
+            FeatureType::Power => {
+                return self
+                    .subfeature(SubfeatureType::Power(Power::Input))
+                    .or_else(|| self.subfeature(SubfeatureType::Power(Power::Average)))
+            }
+            FeatureType::Energy => SubfeatureType::Energy(Energy::Input),
+            FeatureType::Humidity => SubfeatureType::Humidity(Humidity::Input),
+            FeatureType::Cpu => SubfeatureType::Cpu,
+            FeatureType::Intrusion | FeatureType::BeepEnable => return None,
+        };
+
+        self.subfeature(sf_type)
+    }
+
+    /// Read the `<name>_label` attribute. Like libsensors, the label of the `cpuN_vid`
+    /// feature is read from `cpuN_vid_label`, not `cpuN_label`.
     fn read_sysfs_label(&self) -> io::Result<String> {
         let attr = format!("{}_label", self.name);
-        sysfs::sysfs_read_attr(self.dir.as_ref(), attr.as_ref())
+        self.fs.read_attr(&self.dir, &attr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chip::Chip;
+    use crate::context::Context;
+    use crate::sysfs::sysfs_read_file;
+    #[cfg(feature = "sensorsconf")]
+    use crate::testutil::fake_chip_with_context;
+    use crate::testutil::{fake_chip, TempDir};
+    use crate::unit::{Measurement, Unit};
+
+    #[test]
+    fn feature_type_from_str() {
+        assert_eq!("fan".parse::<FeatureType>().unwrap(), FeatureType::Fan);
+        assert_eq!("in".parse::<FeatureType>().unwrap(), FeatureType::Voltage);
+        assert_eq!(
+            "temp".parse::<FeatureType>().unwrap(),
+            FeatureType::Temperature
+        );
+        assert_eq!(
+            "beep_enable".parse::<FeatureType>().unwrap(),
+            FeatureType::BeepEnable
+        );
+        assert!(matches!(
+            "temp1".parse::<FeatureType>(),
+            Err(Error::InvalidValue)
+        ));
+    }
+
+    #[test]
+    fn feature_type_sysfs_prefix() {
+        assert_eq!(FeatureType::Voltage.sysfs_prefix(), "in");
+        assert_eq!(FeatureType::Current.sysfs_prefix(), "curr");
+        assert_eq!(FeatureType::Cpu.sysfs_prefix(), "cpu");
+        for feature_type in [
+            FeatureType::Fan,
+            FeatureType::Pwm,
+            FeatureType::Temperature,
+            FeatureType::Voltage,
+            FeatureType::Current,
+            FeatureType::Power,
+            FeatureType::Energy,
+            FeatureType::Humidity,
+            FeatureType::Cpu,
+            FeatureType::Intrusion,
+            FeatureType::BeepEnable,
+        ] {
+            assert_eq!(
+                feature_type.sysfs_prefix().parse::<FeatureType>().unwrap(),
+                feature_type
+            );
+        }
+    }
+
+    #[test]
+    fn feature_type_display() {
+        assert_eq!(FeatureType::Temperature.to_string(), "Temperature");
+        assert_eq!(FeatureType::Fan.to_string(), "Fan");
+        assert_eq!(FeatureType::Voltage.to_string(), "Voltage");
+        assert_eq!(FeatureType::Cpu.to_string(), "CPU");
+    }
+
+    #[test]
+    fn feature_label_cached() {
+        let (dir, chip) = fake_chip(
+            "dummy",
+            &[
+                ("temp1_input", "45000"),
+                ("temp1_label", "Core 0"),
+                ("in0_input", "1200"),
+            ],
+        );
+
+        let temp = chip.feature(FeatureType::Temperature, 1).unwrap();
+        assert_eq!(temp.label_cached(), Some("Core 0"));
+        dir.file("temp1_label", "Core 1");
+        assert_eq!(temp.label_cached(), Some("Core 0"));
+        assert_eq!(temp.label(), "Core 1");
+
+        let volt = chip.feature(FeatureType::Voltage, 0).unwrap();
+        assert_eq!(volt.label_cached(), None);
+    }
+
+    #[cfg(feature = "sensorsconf")]
+    #[test]
+    fn feature_label_cached_config() {
+        let dir = TempDir::new();
+        let conf = dir.file(
+            "sensors.conf",
+            "chip \"dummy-*\"\n    label temp1 \"CPU\"\n",
+        );
+        let context = Context::with_config(&conf).unwrap();
+        let (_chip_dir, chip) = fake_chip_with_context(
+            &context,
+            "dummy",
+            &[("temp1_input", "45000"), ("temp1_label", "Core 0")],
+        );
+
+        let temp = chip.feature(FeatureType::Temperature, 1).unwrap();
+        assert_eq!(temp.label_cached(), Some("CPU"));
+    }
+
+    #[test]
+    fn feature_type_subfeature_types() {
+        let fan = FeatureType::Fan.subfeature_types();
+        assert!(fan.contains(&SubfeatureType::Fan(Fan::Input)));
+        assert!(fan.contains(&SubfeatureType::Fan(Fan::Target)));
+        assert_eq!(FeatureType::Cpu.subfeature_types(), &[SubfeatureType::Cpu]);
+
+        for ftype in [
+            FeatureType::Fan,
+            FeatureType::Pwm,
+            FeatureType::Temperature,
+            FeatureType::Voltage,
+            FeatureType::Current,
+            FeatureType::Power,
+            FeatureType::Energy,
+            FeatureType::Humidity,
+            FeatureType::Cpu,
+            FeatureType::Intrusion,
+            FeatureType::BeepEnable,
+        ] {
+            assert!(ftype
+                .subfeature_types()
+                .iter()
+                .all(|sf_type| sf_type.feature_type() == ftype));
+        }
+    }
+
+    #[test]
+    fn feature_cpu_vid_name_and_label() {
+        let (dir, chip) = fake_chip("dummy", &[("cpu0_vid", "1250"), ("cpu0_label", "Wrong")]);
+
+        let cpu = chip.feature(FeatureType::Cpu, 0).unwrap();
+        assert_eq!(cpu.name(), "cpu0_vid");
+        assert_eq!(cpu.number(), 0);
+        assert_eq!(
+            cpu.subfeature(SubfeatureType::Cpu).unwrap().path(),
+            dir.path().join("cpu0_vid")
+        );
+        assert_eq!(cpu.label(), "cpu0_vid");
+
+        dir.file("cpu0_vid_label", "VID");
+        assert_eq!(cpu.label(), "VID");
+    }
+
+    #[test]
+    fn feature_subfeatures_iter_len() {
+        let (_dir, chip) = fake_chip(
+            "dummy",
+            &[
+                ("temp1_input", "45000"),
+                ("temp1_max", "80000"),
+                ("temp1_crit", "100000"),
+            ],
+        );
+        let temp = chip.feature(FeatureType::Temperature, 1).unwrap();
+
+        let mut iter = temp.subfeatures_iter();
+        assert_eq!(iter.len(), 3);
+        let last = iter.next_back().unwrap();
+        assert_eq!(iter.len(), 2);
+        assert_eq!(
+            temp.subfeatures_iter().next_back().unwrap().path(),
+            last.path()
+        );
+    }
+
+    /// A getter of a feature value, checked by `feature_value_accessors`.
+    type Accessor = fn(&Feature) -> Option<f64>;
+
+    #[test]
+    fn feature_value_accessors() {
+        let (_dir, chip) = fake_chip(
+            "dummy",
+            &[
+                ("temp1_input", "45000"),
+                ("temp1_max", "80000"),
+                ("fan1_input", "1200"),
+                ("in0_max", "3300"),
+                ("power1_average", "12500000"),
+                ("fan1_pulses", "2"),
+                ("fan1_div", "4"),
+                ("in1_input", "1200"),
+                ("in1_highest", "1250"),
+                ("power2_input", "15000000"),
+                ("power2_input_highest", "42500000"),
+                ("power2_average_highest", "30000000"),
+                ("power1_average_interval", "1500"),
+                ("temp1_crit", "100000"),
+                ("temp1_emergency", "105000"),
+                ("temp2_input", "40000"),
+                ("beep_enable", "1"),
+            ],
+        );
+
+        let cases: &[(&str, Accessor, Option<f64>)] = &[
+            ("temp1", Feature::input_value, Some(45.0)),
+            ("fan1", Feature::input_value, Some(1200.0)),
+            ("in0", Feature::input_value, None),
+            ("power1", Feature::input_value, Some(12.5)),
+            ("beep_enable", Feature::input_value, None),
+            ("fan1", Feature::fan_rpm, Some(1200.0)),
+            ("temp1", Feature::fan_rpm, None),
+            ("in1", Feature::peak_high, Some(1.25)),
+            ("in1", Feature::peak_low, None),
+            ("power2", Feature::peak_high, Some(42.5)),
+            ("power2", Feature::peak_low, None),
+            ("fan1", Feature::peak_high, None),
+            ("power1", Feature::average_interval, Some(1.5)),
+            ("power2", Feature::average_interval, None),
+            ("in1", Feature::average_interval, None),
+            ("temp1", Feature::crit, Some(100.0)),
+            ("temp1", Feature::emergency, Some(105.0)),
+            ("temp2", Feature::crit, None),
+            ("temp2", Feature::emergency, None),
+            ("in1", Feature::crit, None),
+        ];
+        for (i, (name, accessor, expected)) in cases.iter().enumerate() {
+            let feature = chip
+                .features_iter()
+                .find(|feature| feature.name() == *name)
+                .unwrap();
+            assert_eq!(accessor(feature), *expected, "case {} ({})", i, name);
+        }
+    }
+
+    #[test]
+    fn feature_set_beep() {
+        let (dir, chip) = fake_chip(
+            "dummy",
+            &[
+                ("temp1_input", "45000"),
+                ("temp1_beep", "0"),
+                ("in0_input", "1200"),
+            ],
+        );
+        let path = dir.path().join("temp1_beep");
+
+        let temp = chip.feature(FeatureType::Temperature, 1).unwrap();
+        temp.set_beep(true).unwrap();
+        assert_eq!(sysfs_read_file(&path).unwrap(), "1");
+        temp.set_beep(false).unwrap();
+        assert_eq!(sysfs_read_file(&path).unwrap(), "0");
+
+        let volt = chip.feature(FeatureType::Voltage, 0).unwrap();
+        assert!(matches!(volt.set_beep(true), Err(Error::SubfeatureType)));
+    }
+
+    #[test]
+    fn feature_limit_beep_enabled() {
+        let (_dir, chip) = fake_chip(
+            "dummy",
+            &[
+                ("temp1_input", "45000"),
+                ("temp1_max", "80000"),
+                ("temp1_crit", "100000"),
+                ("temp1_beep", "1"),
+                ("temp1_crit_beep", "0"),
+                ("in0_input", "1200"),
+                ("in0_max", "1400"),
+            ],
+        );
+
+        let temp = chip.feature(FeatureType::Temperature, 1).unwrap();
+        let limit = |limit| temp.limit_beep_enabled(SubfeatureType::Temperature(limit));
+        assert_eq!(limit(Temperature::Crit_Max), Some(false));
+        assert_eq!(limit(Temperature::Max), Some(true));
+        assert_eq!(limit(Temperature::Min), Some(true));
+        assert_eq!(
+            temp.limit_beep_enabled(SubfeatureType::Voltage(Voltage::Max)),
+            None
+        );
+
+        let volt = chip.feature(FeatureType::Voltage, 0).unwrap();
+        assert_eq!(
+            volt.limit_beep_enabled(SubfeatureType::Voltage(Voltage::Max)),
+            None
+        );
+    }
+
+    #[test]
+    fn feature_reset_history() {
+        let dir = TempDir::new();
+        dir.file("name", "dummy");
+        dir.file("temp1_input", "45000");
+        dir.file("temp1_highest", "60000");
+        let path = dir.file_with_mode("temp1_reset_history", "", 0o200);
+        dir.file("in0_input", "1200");
+        dir.file_with_mode("in0_reset_history", "", 0o444);
+        dir.file("fan1_input", "1200");
+        let chip = Chip::from_path(dir.path(), None, &Context::empty()).unwrap();
+
+        let temp = chip.feature(FeatureType::Temperature, 1).unwrap();
+        temp.reset_history().unwrap();
+        assert_eq!(sysfs_read_file(&path).unwrap(), "1");
+
+        let volt = chip.feature(FeatureType::Voltage, 0).unwrap();
+        assert!(matches!(volt.reset_history(), Err(Error::Access(_))));
+
+        let fan = chip.feature(FeatureType::Fan, 1).unwrap();
+        assert!(matches!(fan.reset_history(), Err(Error::SubfeatureType)));
+    }
+
+    #[cfg(feature = "sensorsconf")]
+    #[test]
+    fn feature_set_compute_statement() {
+        let (_dir, mut chip) =
+            fake_chip("dummy", &[("temp1_input", "45000"), ("temp1_max", "80000")]);
+        let feature = chip.feature_mut(FeatureType::Temperature, 1).unwrap();
+
+        let input_type = SubfeatureType::Temperature(Temperature::Input);
+        feature
+            .set_compute_statement(input_type, "@*2, @/2")
+            .unwrap();
+        let input = feature.subfeature(input_type).unwrap();
+        assert_eq!(input.read_value().unwrap(), 90.0);
+        assert_eq!(input.compute_statement().as_deref(), Some("@*2, @/2"));
+
+        let max_type = SubfeatureType::Temperature(Temperature::Max);
+        feature
+            .set_compute_statement(max_type, "@-10, @+10")
+            .unwrap();
+        let max = feature.subfeature(max_type).unwrap();
+        assert_eq!(max.read_value().unwrap(), 70.0);
+        max.write_value(60.0).unwrap();
+        assert_eq!(sysfs_read_file(max.path()).unwrap(), "70000");
+
+        assert!(matches!(
+            feature.set_compute_statement(max_type, "@*"),
+            Err(Error::ParseConfig(_))
+        ));
+        assert!(matches!(
+            feature
+                .set_compute_statement(SubfeatureType::Temperature(Temperature::Min), "@*2, @/2"),
+            Err(Error::SubfeatureType)
+        ));
+    }
+
+    #[test]
+    fn feature_read_ordered() {
+        let (_dir, chip) = fake_chip(
+            "dummy",
+            &[
+                ("temp1_max_alarm", "0"),
+                ("temp1_max", "80000"),
+                ("temp1_crit_alarm", "1"),
+                ("temp1_input", "45000"),
+                ("temp1_crit", "100000"),
+            ],
+        );
+        let temp = chip.feature(FeatureType::Temperature, 1).unwrap();
+        assert_eq!(
+            temp.read_ordered(),
+            vec![
+                (SubfeatureType::Temperature(Temperature::Input), 45.0),
+                (SubfeatureType::Temperature(Temperature::Max), 80.0),
+                (SubfeatureType::Temperature(Temperature::Crit_Max), 100.0),
+                (SubfeatureType::Temperature(Temperature::Max_Alarm), 0.0),
+                (
+                    SubfeatureType::Temperature(Temperature::Crit_Max_Alarm),
+                    1.0
+                ),
+            ]
+        );
+
+        let (_dir, chip) = fake_chip(
+            "dummy",
+            &[
+                ("power1_cap_alarm", "0"),
+                ("power1_average_interval", "1000"),
+                ("power1_cap", "95000000"),
+                ("power1_input", "15000000"),
+                ("power1_average_highest", "30000000"),
+                ("power1_average", "12500000"),
+            ],
+        );
+        let power = chip.feature(FeatureType::Power, 1).unwrap();
+        assert_eq!(
+            power.read_ordered(),
+            vec![
+                (SubfeatureType::Power(Power::Average), 12.5),
+                (SubfeatureType::Power(Power::Input), 15.0),
+                (SubfeatureType::Power(Power::Cap), 95.0),
+                (SubfeatureType::Power(Power::Average_Highest), 30.0),
+                (SubfeatureType::Power(Power::Average_Interval), 1.0),
+                (SubfeatureType::Power(Power::Cap_Alarm), 0.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn feature_temperature_status() {
+        let (dir, chip) = fake_chip(
+            "dummy",
+            &[
+                ("temp1_input", "45000"),
+                ("temp1_fault", "0"),
+                ("temp2_input", "-128000"),
+                ("temp2_fault", "1"),
+                ("temp3_input", "50000"),
+                ("temp4_max", "80000"),
+                ("in0_input", "1200"),
+            ],
+        );
+        let status = |number| {
+            chip.feature(FeatureType::Temperature, number)
+                .unwrap()
+                .temperature_status()
+        };
+        assert_eq!(status(1), TempStatus::Ok(45.0));
+        assert_eq!(status(2), TempStatus::Fault);
+        assert_eq!(status(4), TempStatus::Missing);
+
+        std::fs::remove_file(dir.path().join("temp3_input")).unwrap();
+        assert_eq!(status(3), TempStatus::Missing);
+
+        let volt = chip.feature(FeatureType::Voltage, 0).unwrap();
+        assert_eq!(volt.temperature_status(), TempStatus::Missing);
+    }
+
+    #[test]
+    fn feature_power_cap() {
+        let (dir, chip) = fake_chip(
+            "dummy",
+            &[
+                ("power1_input", "12000000"),
+                ("power1_cap", "65000000"),
+                ("power1_cap_min", "10000000"),
+                ("power1_cap_max", "125000000"),
+            ],
+        );
+        let power = chip.feature(FeatureType::Power, 1).unwrap();
+        assert_eq!(power.read_power_cap().unwrap(), 65.0);
+        assert_eq!(power.power_cap_range(), Some((10.0, 125.0)));
+
+        power.write_power_cap(95.0).unwrap();
+        assert_eq!(
+            sysfs_read_file(&dir.path().join("power1_cap")).unwrap(),
+            "95000000"
+        );
+
+        assert!(matches!(
+            power.write_power_cap(150.0),
+            Err(Error::OutOfRange { max, .. }) if max == 125.0
+        ));
+        assert_eq!(power.read_power_cap().unwrap(), 95.0);
+    }
+
+    #[test]
+    fn feature_auto_points() {
+        let (dir, chip) = fake_chip(
+            "dummy",
+            &[
+                ("pwm1", "128"),
+                ("pwm1_auto_point2_temp", "70000"),
+                ("pwm1_auto_point2_pwm", "255"),
+                ("pwm1_auto_point1_temp", "40000"),
+                ("pwm1_auto_point1_pwm", "51"),
+                ("pwm1_auto_point3_temp", "90000"),
+                ("temp1_input", "45000"),
+            ],
+        );
+
+        let pwm = chip.feature(FeatureType::Pwm, 1).unwrap();
+        assert_eq!(pwm.auto_points(), vec![(40.0, 51.0), (70.0, 255.0)]);
+
+        pwm.write_auto_point(1, 45.5, 64.0).unwrap();
+        assert_eq!(
+            sysfs_read_file(&dir.path().join("pwm1_auto_point1_temp")).unwrap(),
+            "45500"
+        );
+        assert_eq!(pwm.auto_points(), vec![(45.5, 64.0), (70.0, 255.0)]);
+        assert!(matches!(
+            pwm.write_auto_point(3, 50.0, 128.0),
+            Err(Error::SubfeatureType)
+        ));
+        assert!(matches!(
+            pwm.write_auto_point(1, 50.0, 300.0),
+            Err(Error::InvalidValue)
+        ));
+        assert!(matches!(
+            pwm.write_auto_point(1, f64::NAN, 128.0),
+            Err(Error::InvalidValue)
+        ));
+        assert_eq!(pwm.auto_points(), vec![(45.5, 64.0), (70.0, 255.0)]);
+
+        dir.file_with_mode("pwm1_auto_point2_pwm", "255", 0o444);
+        assert!(matches!(
+            pwm.write_auto_point(2, 80.0, 300.0),
+            Err(Error::Access(_))
+        ));
+        assert_eq!(pwm.auto_points(), vec![(45.5, 64.0), (70.0, 255.0)]);
+
+        let temp = chip.feature(FeatureType::Temperature, 1).unwrap();
+        assert!(temp.auto_points().is_empty());
+    }
+
+    #[test]
+    fn feature_fan_target() {
+        let (dir, chip) = fake_chip("dummy", &[("fan1_input", "1187"), ("fan1_target", "1200")]);
+
+        let fan = chip.feature(FeatureType::Fan, 1).unwrap();
+        assert_eq!(fan.read_fan_target().unwrap(), 1200.0);
+        fan.write_fan_target(900.0).unwrap();
+        assert_eq!(
+            sysfs_read_file(&dir.path().join("fan1_target")).unwrap(),
+            "900"
+        );
+        assert_eq!(fan.read_fan_target().unwrap(), 900.0);
+
+        let (_dir, chip) = fake_chip("dummy", &[("fan1_input", "1187")]);
+        let fan = chip.feature(FeatureType::Fan, 1).unwrap();
+        assert!(matches!(fan.read_fan_target(), Err(Error::SubfeatureType)));
+    }
+
+    #[test]
+    fn feature_humidity_limits_and_fault() {
+        let (dir, chip) = fake_chip(
+            "dummy",
+            &[
+                ("humidity1_input", "45500"),
+                ("humidity1_min", "20000"),
+                ("humidity1_min_alarm", "1"),
+                ("humidity1_fault", "0"),
+            ],
+        );
+
+        let humidity = chip.feature(FeatureType::Humidity, 1).unwrap();
+        let min = humidity
+            .subfeature(SubfeatureType::Humidity(Humidity::Min))
+            .unwrap();
+        assert_eq!(
+            min.read_measurement().unwrap(),
+            Measurement {
+                value: 20.0,
+                unit: Unit::Percent
+            }
+        );
+        assert_eq!(
+            humidity.active_alarms(),
+            [SubfeatureType::Humidity(Humidity::Min_Alarm)]
+        );
+        assert_eq!(
+            humidity.iter_limits().collect::<Vec<_>>(),
+            [(SubfeatureType::Humidity(Humidity::Min), 20.0, None)]
+        );
+
+        assert_eq!(humidity.fault(), Some(false));
+        dir.file("humidity1_fault", "1");
+        assert_eq!(humidity.fault(), Some(true));
+        let (_dir, chip) = fake_chip("dummy", &[("in0_input", "1200")]);
+        assert_eq!(chip.feature(FeatureType::Voltage, 0).unwrap().fault(), None);
+    }
+
+    #[test]
+    fn feature_iter_limits() {
+        let (_dir, chip) = fake_chip(
+            "dummy",
+            &[
+                ("temp1_input", "45000"),
+                ("temp1_crit", "100000"),
+                ("temp1_max", "80000"),
+                ("temp1_max_hyst", "75000"),
+                ("temp1_lowest", "20000"),
+                ("temp1_crit_alarm", "0"),
+            ],
+        );
+
+        let temp = chip.feature(FeatureType::Temperature, 1).unwrap();
+        let limits: Vec<_> = temp.iter_limits().collect();
+        assert_eq!(
+            limits,
+            [
+                (
+                    SubfeatureType::Temperature(Temperature::Max),
+                    80.0,
+                    Some(75.0)
+                ),
+                (
+                    SubfeatureType::Temperature(Temperature::Crit_Max),
+                    100.0,
+                    None
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn feature_limit_with_hyst() {
+        let (_dir, chip) = fake_chip(
+            "dummy",
+            &[
+                ("temp1_input", "45000"),
+                ("temp1_crit", "100000"),
+                ("temp1_crit_hyst", "95000"),
+                ("temp1_max", "80000"),
+                ("temp1_lowest", "20000"),
+            ],
+        );
+        let temp = chip.feature(FeatureType::Temperature, 1).unwrap();
+
+        assert_eq!(
+            temp.limit_with_hyst(SubfeatureType::Temperature(Temperature::Crit_Max)),
+            Some((100.0, Some(95.0)))
+        );
+        assert_eq!(
+            temp.limit_with_hyst(SubfeatureType::Temperature(Temperature::Max)),
+            Some((80.0, None))
+        );
+        assert_eq!(
+            temp.limit_with_hyst(SubfeatureType::Temperature(Temperature::Lowest)),
+            Some((20.0, None))
+        );
+        assert_eq!(
+            temp.limit_with_hyst(SubfeatureType::Temperature(Temperature::Min)),
+            None
+        );
+    }
+
+    #[test]
+    fn feature_temp_type() {
+        let (dir, chip) = fake_chip("dummy", &[("temp1_input", "45000"), ("temp1_type", "3")]);
+        let temp = chip.feature(FeatureType::Temperature, 1).unwrap();
+        assert_eq!(temp.read_temp_type().unwrap(), TempSensorKind::ThermalDiode);
+
+        temp.write_temp_type(TempSensorKind::Thermistor).unwrap();
+        assert_eq!(
+            sysfs_read_file(&dir.path().join("temp1_type")).unwrap(),
+            "4"
+        );
+        assert_eq!(temp.read_temp_type().unwrap(), TempSensorKind::Thermistor);
+
+        assert!(matches!(
+            temp.write_temp_type(TempSensorKind::Unknown),
+            Err(Error::InvalidValue)
+        ));
+
+        dir.file("temp1_type", "3435");
+        assert_eq!(temp.read_temp_type().unwrap(), TempSensorKind::Thermistor);
+    }
+
+    #[test]
+    fn feature_write_hyst() {
+        let (dir, chip) = fake_chip(
+            "dummy",
+            &[
+                ("temp1_input", "45000"),
+                ("temp1_max", "80000"),
+                ("temp1_max_hyst", "75000"),
+                ("temp1_crit", "100000"),
+            ],
+        );
+        let temp = chip.feature(FeatureType::Temperature, 1).unwrap();
+        let max = SubfeatureType::Temperature(Temperature::Max);
+
+        temp.write_hyst(max, 70.0).unwrap();
+        assert_eq!(
+            sysfs_read_file(&dir.path().join("temp1_max_hyst")).unwrap(),
+            "70000"
+        );
+        assert_eq!(temp.limit_with_hyst(max), Some((80.0, Some(70.0))));
+
+        assert!(matches!(
+            temp.write_hyst(SubfeatureType::Temperature(Temperature::Crit_Max), 95.0),
+            Err(Error::SubfeatureType)
+        ));
+        assert!(matches!(
+            temp.write_hyst(SubfeatureType::Temperature(Temperature::Input), 40.0),
+            Err(Error::SubfeatureType)
+        ));
+    }
+
+    #[test]
+    fn feature_active_alarms() {
+        let (_dir, chip) = fake_chip(
+            "dummy",
+            &[
+                ("in0_input", "1500"),
+                ("in0_min_alarm", "0"),
+                ("in0_max_alarm", "1"),
+            ],
+        );
+        let volt = chip.feature(FeatureType::Voltage, 0).unwrap();
+
+        assert_eq!(
+            volt.active_alarms(),
+            vec![SubfeatureType::Voltage(Voltage::Max_Alarm)]
+        );
+
+        let alarm = |sf_type| volt.subfeature(sf_type).unwrap().is_alarm_active().unwrap();
+        assert!(alarm(SubfeatureType::Voltage(Voltage::Max_Alarm)));
+        assert!(!alarm(SubfeatureType::Voltage(Voltage::Min_Alarm)));
+        assert!(!alarm(SubfeatureType::Voltage(Voltage::Input)));
     }
 }
@@ -0,0 +1,87 @@
+// SPDX-FileCopyrightText: 2026 Camille019
+// SPDX-License-Identifier: MPL-2.0
+
+use std::io::{self, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::chip::Chip;
+use crate::subfeature::Subfeature;
+
+impl Chip {
+    /// The readable input subfeatures of the chip, one per CSV column.
+    fn csv_columns(&self) -> impl Iterator<Item = &Subfeature> {
+        self.features_iter()
+            .filter_map(|feature| feature.input_subfeature())
+            .filter(|subfeature| subfeature.is_readable())
+    }
+
+    /// Write the CSV header matching `write_csv_row`: a `timestamp` column, then one
+    /// column per readable input subfeature, named after the chip and the subfeature,
+    /// like `coretemp-isa-0000/temp1_input`.
+    pub fn write_csv_header(&self, w: &mut impl Write) -> io::Result<()> {
+        let name = self.name();
+        write!(w, "timestamp")?;
+        for subfeature in self.csv_columns() {
+            write!(w, ",{}/{}", name, subfeature.name())?;
+        }
+        writeln!(w)
+    }
+
+    /// Write a CSV row with the current readings of the chip, starting with the number
+    /// of seconds since the Unix epoch. A value which can not be read is left empty.
+    pub fn write_csv_row(&self, w: &mut impl Write) -> io::Result<()> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        write!(w, "{:.3}", timestamp.as_secs_f64())?;
+        for subfeature in self.csv_columns() {
+            match subfeature.read_value() {
+                Ok(value) => write!(w, ",{}", value)?,
+                Err(_) => write!(w, ",")?,
+            }
+        }
+        writeln!(w)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testutil::fake_chip;
+
+    #[test]
+    fn chip_csv_header_and_row() {
+        let (dir, chip) = fake_chip(
+            "dummy",
+            &[
+                ("temp1_input", "45000"),
+                ("temp1_max", "80000"),
+                ("in0_input", "1200"),
+                ("fan1_input", "1250"),
+            ],
+        );
+        std::fs::remove_file(dir.path().join("in0_input")).unwrap();
+
+        let mut buf = Vec::new();
+        chip.write_csv_header(&mut buf).unwrap();
+        chip.write_csv_row(&mut buf).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+        let lines: Vec<Vec<&str>> = output
+            .lines()
+            .map(|line| line.split(',').collect())
+            .collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0],
+            vec![
+                "timestamp",
+                "dummy-virtual-0/fan1_input",
+                "dummy-virtual-0/temp1_input",
+                "dummy-virtual-0/in0_input",
+            ]
+        );
+        assert_eq!(lines[1].len(), lines[0].len());
+        assert!(lines[1][0].parse::<f64>().unwrap() > 0.0);
+        assert_eq!(&lines[1][1..], ["1250", "45", ""]);
+    }
+}
@@ -0,0 +1,18 @@
+// SPDX-FileCopyrightText: 2026 Camille019
+// SPDX-License-Identifier: MPL-2.0
+
+//! Export of the sensor tree to various text formats.
+
+mod csv;
+#[cfg(feature = "prometheus")]
+mod prometheus;
+mod report;
+#[cfg(feature = "serde")]
+mod toml;
+
+#[cfg(feature = "prometheus")]
+pub use self::prometheus::to_prometheus;
+pub use self::report::scale_value;
+
+#[cfg(feature = "serde")]
+pub use self::toml::to_toml;
@@ -0,0 +1,183 @@
+// SPDX-FileCopyrightText: 2026 Camille019
+// SPDX-License-Identifier: MPL-2.0
+
+use std::fmt::Write;
+
+use crate::chip::Chip;
+use crate::subfeature::Subfeature;
+use crate::unit::Unit;
+
+/// Base unit suffix of a metric name, following the Prometheus naming conventions.
+fn unit_suffix(unit: Unit) -> &'static str {
+    match unit {
+        Unit::Celsius => "_celsius",
+        Unit::Volt => "_volts",
+        Unit::Ampere => "_amperes",
+        Unit::Watt => "_watts",
+        Unit::Joule => "_joules",
+        Unit::Rpm => "_rpm",
+        Unit::Percent => "_percent",
+        Unit::Hertz => "_hertz",
+        Unit::Second => "_seconds",
+        Unit::Dimensionless => "",
+    }
+}
+
+/// Metric name of a subfeature, built from its sysfs name without the feature
+/// number nor the `input` suffix, e.g. `hwmon_temp_max_celsius` for `temp1_max`
+/// and `hwmon_temp_celsius` for `temp1_input`.
+fn metric_name(subfeature: &Subfeature) -> String {
+    let (feature, suffix) = subfeature
+        .name()
+        .split_once('_')
+        .unwrap_or((subfeature.name(), ""));
+    let mut name = String::from("hwmon_");
+    name.push_str(feature.trim_end_matches(|c: char| c.is_ascii_digit()));
+    if !suffix.is_empty() && suffix != "input" {
+        name.push('_');
+        name.push_str(suffix);
+    }
+    name.push_str(unit_suffix(subfeature.get_type().unit()));
+    name
+}
+
+/// Escape a label value of the text exposition format.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Write the samples of the readable subfeatures of the chips to `buf`, in the
+/// Prometheus text exposition format, grouped by metric name.
+///
+/// Inputs and limits are exported as gauges with their unit in the metric name, like
+/// `hwmon_temp_celsius{chip="coretemp-isa-0000",hwmon="hwmon1",sensor="temp1"} 45.0`.
+/// The `hwmon` label, the chip directory, tells apart chips with the same name.
+/// Alarms are exported as `hwmon_*_alarm` gauges, with the value 0 or 1. Other flags
+/// and modes are skipped. No `# TYPE` line is emitted.
+///
+/// The exposition format requires the samples of a metric to be consecutive, so the
+/// outputs of `Chip::to_prometheus` for several chips can not be concatenated.
+pub fn to_prometheus(chips: &[Chip], buf: &mut String) {
+    // Metric names in order of first appearance, with their samples.
+    let mut metrics: Vec<(String, Vec<String>)> = Vec::new();
+
+    for chip in chips {
+        let name = escape_label(&chip.name());
+        let dir = chip
+            .path()
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy();
+        let dir = escape_label(&dir);
+
+        for feature in chip.features_iter() {
+            let mut subfeatures: Vec<&Subfeature> = feature
+                .subfeatures_iter()
+                .filter(|sf| {
+                    let sf_type = sf.get_type();
+                    sf_type.is_alarm() || sf_type.unit() != Unit::Dimensionless
+                })
+                .collect();
+            subfeatures.sort_by_key(|sf| sf.get_type());
+
+            for subfeature in subfeatures {
+                if let Ok(value) = subfeature.read_value() {
+                    let sample = format!(
+                        "{{chip=\"{}\",hwmon=\"{}\",sensor=\"{}\"}} {:?}",
+                        name,
+                        dir,
+                        escape_label(feature.name()),
+                        value
+                    );
+                    let metric = metric_name(subfeature);
+                    match metrics.iter_mut().find(|(name, _)| *name == metric) {
+                        Some((_, samples)) => samples.push(sample),
+                        None => metrics.push((metric, vec![sample])),
+                    }
+                }
+            }
+        }
+    }
+
+    for (metric, samples) in metrics {
+        for sample in samples {
+            let _ = writeln!(buf, "{}{}", metric, sample);
+        }
+    }
+}
+
+impl Chip {
+    /// Append the readable subfeatures of the chip to `buf`, in the Prometheus text
+    /// exposition format, see `hwmon::format::to_prometheus`.
+    pub fn to_prometheus(&self, buf: &mut String) {
+        to_prometheus(std::slice::from_ref(self), buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::Context;
+    use crate::mock::MockFs;
+
+    #[test]
+    fn chip_to_prometheus() {
+        let mut mock = MockFs::new();
+        mock.dir("/sys/class/i2c-adapter");
+        for (name, contents) in [
+            ("name", "dummy"),
+            ("temp1_input", "45000"),
+            ("temp1_max", "80000"),
+            ("temp1_max_alarm", "1"),
+            ("temp1_type", "4"),
+            ("temp2_input", "50000"),
+            ("temp2_max", "90000"),
+            ("fan1_input", "1250"),
+        ] {
+            mock.file(format!("/sys/class/hwmon/hwmon0/{}", name), contents);
+        }
+        let context = Context::with_mock(mock).unwrap();
+        let chips = context.chips().unwrap();
+
+        let mut buf = String::new();
+        chips[0].to_prometheus(&mut buf);
+
+        let expected = "\
+hwmon_fan_rpm{chip=\"dummy-virtual-0\",hwmon=\"hwmon0\",sensor=\"fan1\"} 1250.0
+hwmon_temp_celsius{chip=\"dummy-virtual-0\",hwmon=\"hwmon0\",sensor=\"temp1\"} 45.0
+hwmon_temp_celsius{chip=\"dummy-virtual-0\",hwmon=\"hwmon0\",sensor=\"temp2\"} 50.0
+hwmon_temp_max_celsius{chip=\"dummy-virtual-0\",hwmon=\"hwmon0\",sensor=\"temp1\"} 80.0
+hwmon_temp_max_celsius{chip=\"dummy-virtual-0\",hwmon=\"hwmon0\",sensor=\"temp2\"} 90.0
+hwmon_temp_max_alarm{chip=\"dummy-virtual-0\",hwmon=\"hwmon0\",sensor=\"temp1\"} 1.0
+";
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    fn chips_to_prometheus_same_name() {
+        let mut mock = MockFs::new();
+        mock.dir("/sys/class/i2c-adapter");
+        mock.file("/sys/class/hwmon/hwmon0/name", "acpitz");
+        mock.file("/sys/class/hwmon/hwmon0/temp1_input", "45000");
+        mock.file("/sys/class/hwmon/hwmon0/temp1_crit", "100000");
+        mock.file("/sys/class/hwmon/hwmon1/name", "acpitz");
+        mock.file("/sys/class/hwmon/hwmon1/temp1_input", "50000");
+        mock.file("/sys/class/hwmon/hwmon1/temp1_crit", "105000");
+        let context = Context::with_mock(mock).unwrap();
+        let chips = context.chips().unwrap();
+
+        let mut buf = String::new();
+        to_prometheus(chips, &mut buf);
+
+        let expected = "\
+hwmon_temp_celsius{chip=\"acpitz-virtual-0\",hwmon=\"hwmon0\",sensor=\"temp1\"} 45.0
+hwmon_temp_celsius{chip=\"acpitz-virtual-0\",hwmon=\"hwmon1\",sensor=\"temp1\"} 50.0
+hwmon_temp_crit_celsius{chip=\"acpitz-virtual-0\",hwmon=\"hwmon0\",sensor=\"temp1\"} 100.0
+hwmon_temp_crit_celsius{chip=\"acpitz-virtual-0\",hwmon=\"hwmon1\",sensor=\"temp1\"} 105.0
+";
+        assert_eq!(buf, expected);
+    }
+}
@@ -0,0 +1,296 @@
+// SPDX-FileCopyrightText: 2026 Camille019
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::chip::Chip;
+use crate::feature::{Feature, FeatureType};
+use crate::subfeature::{Fan, Pwm, PwmEnable, SubfeatureType};
+use crate::unit::Unit;
+
+/// Smallest width of the label column, colon included.
+const MIN_LABEL_WIDTH: usize = 12;
+
+/// Width of the column of the main value of a feature.
+const VALUE_WIDTH: usize = 10;
+
+/// SI prefixes chosen by `scale_value`, from the largest factor down.
+const SI_PREFIXES: [(f64, &str); 7] = [
+    (1e9, "G"),
+    (1e6, "M"),
+    (1e3, "k"),
+    (1.0, ""),
+    (1e-3, "m"),
+    (1e-6, "u"),
+    (1e-9, "n"),
+];
+
+/// Scale a value to keep it in the `[1, 1000[` range, and return the scaled value
+/// with its SI prefix symbol, from `n` to `G`.
+///
+/// Zero and non-finite values are returned unchanged, without prefix.
+///
+/// ```
+/// assert_eq!(hwmon::format::scale_value(0.0125), (12.5, "m"));
+/// assert_eq!(hwmon::format::scale_value(42.0), (42.0, ""));
+/// assert_eq!(hwmon::format::scale_value(2500.0), (2.5, "k"));
+/// ```
+pub fn scale_value(value: f64) -> (f64, &'static str) {
+    if value == 0.0 || !value.is_finite() {
+        return (value, "");
+    }
+    let magnitude = value.abs();
+    let (factor, prefix) = SI_PREFIXES
+        .iter()
+        .find(|(factor, _)| magnitude >= *factor)
+        .unwrap_or(&SI_PREFIXES[SI_PREFIXES.len() - 1]);
+    (value / factor, prefix)
+}
+
+/// Render the value of a subfeature with its unit: signed temperatures, voltages and
+/// currents, whole fan speeds, SI prefixes for power and energy, and pwm duty cycles
+/// as a percentage.
+fn format_value(sf_type: SubfeatureType, value: f64) -> String {
+    let unit = sf_type.unit();
+    match unit {
+        _ if sf_type == SubfeatureType::Pwm(Pwm::Pwm) => {
+            format!("{:.1}%", value * 100.0 / 255.0)
+        }
+        Unit::Percent if sf_type.feature_type() == FeatureType::Humidity => {
+            format!("{:.1} %RH", value)
+        }
+        Unit::Celsius => format!("{:+.1}{}", value, unit.symbol()),
+        Unit::Volt | Unit::Ampere => format!("{:+.2} {}", value, unit.symbol()),
+        Unit::Watt | Unit::Joule => {
+            let (value, prefix) = scale_value(value);
+            format!("{:.2} {}{}", value, prefix, unit.symbol())
+        }
+        Unit::Rpm => format!("{:.0} {}", value, unit.symbol()),
+        Unit::Percent => format!("{:.1}{}", value, unit.symbol()),
+        Unit::Hertz | Unit::Second => format!("{:.2} {}", value, unit.symbol()),
+        Unit::Dimensionless => value.to_string(),
+    }
+}
+
+/// Return the suffix of the sysfs attribute name of a subfeature type, like `crit`
+/// for `temp1_crit`.
+fn attribute_suffix(sf_type: SubfeatureType) -> String {
+    let name = sf_type.canonical_name();
+    match name.split_once('_') {
+        Some((_, suffix)) => suffix.to_owned(),
+        None => String::new(),
+    }
+}
+
+/// Render the main value of a feature, or `N/A` when it can not be read.
+fn feature_value(feature: &Feature) -> String {
+    match feature.get_type() {
+        FeatureType::Intrusion => {
+            return if feature.active_alarms().is_empty() {
+                "OK".to_owned()
+            } else {
+                "ALARM".to_owned()
+            };
+        }
+        FeatureType::BeepEnable => {
+            return match feature.subfeature(SubfeatureType::BeepEnable) {
+                Some(beep) if beep.read_value().is_ok_and(|value| value == 0.0) => {
+                    "disabled".to_owned()
+                }
+                Some(beep) if beep.read_value().is_ok() => "enabled".to_owned(),
+                _ => "N/A".to_owned(),
+            };
+        }
+        _ => {}
+    }
+    if feature.fault() == Some(true) {
+        return "FAULT".to_owned();
+    }
+    feature
+        .input_subfeature()
+        .and_then(|input| Some(format_value(input.get_type(), input.read_value().ok()?)))
+        .unwrap_or_else(|| "N/A".to_owned())
+}
+
+/// Render the limits of a feature, each followed by its hysteresis, and the enable
+/// mode of a pwm feature.
+fn feature_details(feature: &Feature) -> Vec<String> {
+    let mut details = Vec::new();
+    for (limit, value, hyst) in feature.iter_limits() {
+        details.push(format!(
+            "{} = {}",
+            attribute_suffix(limit),
+            format_value(limit, value)
+        ));
+        if let (Some(hyst_type), Some(hyst)) = (limit.hysteresis(), hyst) {
+            details.push(format!("hyst = {}", format_value(hyst_type, hyst)));
+        }
+    }
+    if let Some(target) = feature.subfeature(SubfeatureType::Fan(Fan::Target)) {
+        if let Ok(value) = target.read_value() {
+            details.push(format!(
+                "target = {}",
+                format_value(target.get_type(), value)
+            ));
+        }
+    }
+    if let Some(enable) = feature.subfeature(SubfeatureType::Pwm(Pwm::Enable)) {
+        let mode = match enable.read_pwm_enable() {
+            Ok(PwmEnable::NoControl) => Some("off"),
+            Ok(PwmEnable::Manual) => Some("manual"),
+            Ok(PwmEnable::Automatic(_)) => Some("auto"),
+            Err(_) => None,
+        };
+        if let Some(mode) = mode {
+            details.push(format!("enable = {}", mode));
+        }
+    }
+    details
+}
+
+/// Render the raised alarms of a feature, like `ALARM (MAX, CRIT)`, or `None` if no
+/// alarm is raised. Intrusion alarms are the main value of their feature instead.
+fn feature_alarms(feature: &Feature) -> Option<String> {
+    if feature.get_type() == FeatureType::Intrusion {
+        return None;
+    }
+    let alarms = feature.active_alarms();
+    if alarms.is_empty() {
+        return None;
+    }
+    let names: Vec<String> = alarms
+        .into_iter()
+        .filter_map(|alarm| {
+            let suffix = attribute_suffix(alarm);
+            let name = suffix.strip_suffix("_alarm")?;
+            Some(name.to_uppercase())
+        })
+        .collect();
+    if names.is_empty() {
+        Some("ALARM".to_owned())
+    } else {
+        Some(format!("ALARM ({})", names.join(", ")))
+    }
+}
+
+/// Render the line of a feature, its label padded to `label_width`.
+fn feature_line(feature: &Feature, label_width: usize) -> String {
+    let label = format!("{}:", feature.label());
+    let mut line = format!(
+        "{:<lw$}{:>vw$}",
+        label,
+        feature_value(feature),
+        lw = label_width,
+        vw = VALUE_WIDTH,
+    );
+    let details = feature_details(feature);
+    if !details.is_empty() {
+        line.push_str("  (");
+        line.push_str(&details.join(", "));
+        line.push(')');
+    }
+    if let Some(alarms) = feature_alarms(feature) {
+        line.push_str("  ");
+        line.push_str(&alarms);
+    }
+    line
+}
+
+impl Chip {
+    /// Render a text report of the chip, in the layout of the lm-sensors `sensors`
+    /// program: the chip name, its adapter, then one line per feature with its label,
+    /// its value, its limits and its raised alarms.
+    ///
+    /// Power and energy values are scaled with `scale_value`. A value which can not be
+    /// read is shown as `N/A`.
+    pub fn format_report(&self) -> String {
+        let label_width = self
+            .features_iter()
+            .map(|feature| feature.label().len() + 2)
+            .max()
+            .unwrap_or(0)
+            .max(MIN_LABEL_WIDTH);
+
+        let mut report = format!("{}\n", self.name());
+        if let Some(adapter) = self.bus().adapter_name() {
+            report.push_str(&format!("Adapter: {}\n", adapter));
+        }
+        for feature in self.features_iter() {
+            report.push_str(&feature_line(feature, label_width));
+            report.push('\n');
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::fake_chip;
+
+    #[test]
+    fn scale_value_prefixes() {
+        assert_eq!(scale_value(0.0), (0.0, ""));
+        assert_eq!(scale_value(0.5), (500.0, "m"));
+        assert_eq!(scale_value(1.0), (1.0, ""));
+        assert_eq!(scale_value(-65.0), (-65.0, ""));
+        assert_eq!(scale_value(1.5e6), (1.5, "M"));
+        assert_eq!(scale_value(3e-9), (3.0, "n"));
+        assert_eq!(scale_value(4e12), (4000.0, "G"));
+    }
+
+    #[test]
+    fn chip_format_report() {
+        let (_dir, chip) = fake_chip(
+            "dummy",
+            &[
+                ("temp1_input", "45000"),
+                ("temp1_label", "Package id 0"),
+                ("temp1_max", "80000"),
+                ("temp1_max_hyst", "75000"),
+                ("temp1_crit", "100000"),
+                ("in0_input", "1200"),
+                ("in0_min", "1000"),
+                ("in0_max", "1100"),
+                ("in0_max_alarm", "1"),
+                ("fan1_input", "1187"),
+                ("fan1_min", "600"),
+                ("fan1_target", "1200"),
+                ("fan2_input", "0"),
+                ("fan2_fault", "1"),
+                ("pwm1", "128"),
+                ("pwm1_enable", "1"),
+                ("power1_input", "12500000"),
+                ("energy1_input", "2500000000"),
+                ("humidity1_input", "45500"),
+                ("intrusion0_alarm", "1"),
+            ],
+        );
+
+        let expected = "\
+dummy-virtual-0
+Adapter: Virtual device
+fan1:           1187 RPM  (min = 600 RPM, target = 1200 RPM)
+fan2:              FAULT
+pwm1:              50.2%  (enable = manual)
+Package id 0:    +45.0°C  (max = +80.0°C, hyst = +75.0°C, crit = +100.0°C)
+in0:             +1.20 V  (max = +1.10 V, min = +1.00 V)  ALARM (MAX)
+power1:          12.50 W
+energy1:         2.50 kJ
+humidity1:      45.5 %RH
+intrusion0:        ALARM
+";
+        assert_eq!(chip.format_report(), expected);
+    }
+
+    #[test]
+    fn chip_format_report_unreadable() {
+        let (dir, chip) = fake_chip("dummy", &[("temp1_input", "45000"), ("temp1_max", "80000")]);
+        std::fs::remove_file(dir.path().join("temp1_input")).unwrap();
+
+        let expected = "\
+dummy-virtual-0
+Adapter: Virtual device
+temp1:             N/A  (max = +80.0°C)
+";
+        assert_eq!(chip.format_report(), expected);
+    }
+}
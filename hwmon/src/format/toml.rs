@@ -0,0 +1,135 @@
+// SPDX-FileCopyrightText: 2026 Camille019
+// SPDX-License-Identifier: MPL-2.0
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::chip::Chip;
+use crate::error::Error;
+use crate::feature::Feature;
+
+#[derive(Serialize)]
+struct ChipTable<'a> {
+    prefix: &'a str,
+    path: String,
+    bus: String,
+    bus_number: i32,
+    address: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    adapter: Option<String>,
+    #[serde(flatten)]
+    features: BTreeMap<&'a str, FeatureTable>,
+}
+
+#[derive(Serialize)]
+struct FeatureTable {
+    label: String,
+    #[serde(flatten)]
+    values: BTreeMap<String, f64>,
+}
+
+impl<'a> ChipTable<'a> {
+    fn new(chip: &'a Chip) -> ChipTable<'a> {
+        ChipTable {
+            prefix: chip.prefix(),
+            path: chip.path().display().to_string(),
+            bus: chip.bus().get_type().to_string(),
+            bus_number: chip.bus().number(),
+            address: chip.address(),
+            adapter: chip.bus().adapter_name(),
+            features: chip
+                .features_iter()
+                .map(|feature| (feature.name(), FeatureTable::new(feature)))
+                .collect(),
+        }
+    }
+}
+
+impl FeatureTable {
+    fn new(feature: &Feature) -> FeatureTable {
+        FeatureTable {
+            label: feature.label(),
+            values: feature
+                .subfeatures_iter()
+                .filter_map(|sf| sf.read_value().ok().map(|v| (sf.name().to_owned(), v)))
+                .collect(),
+        }
+    }
+}
+
+/// Serialize the chips, their features and the current subfeature values to a TOML document.
+///
+/// Each chip is a table keyed by its name, with a sub-table per feature. Several chips
+/// can have the same name, like two virtual `acpitz` chips: their tables are keyed by
+/// the name and the hwmon directory instead, like `acpitz-virtual-0@hwmon2`.
+/// Subfeatures that can not be read are omitted.
+pub fn to_toml(chips: &[Chip]) -> Result<String, Error> {
+    let names: Vec<String> = chips.iter().map(Chip::name).collect();
+    let doc: BTreeMap<String, ChipTable> = chips
+        .iter()
+        .zip(&names)
+        .map(|(chip, name)| {
+            let key = if names.iter().filter(|other| *other == name).count() > 1 {
+                let dir = chip.path().file_name().unwrap_or_default();
+                format!("{}@{}", name, dir.to_string_lossy())
+            } else {
+                name.clone()
+            };
+            (key, ChipTable::new(chip))
+        })
+        .collect();
+
+    Ok(::toml::to_string(&doc)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::fake_chip;
+
+    #[test]
+    fn to_toml_synthetic_chip() {
+        let (_dir, chip) = fake_chip(
+            "dummy",
+            &[
+                ("temp1_input", "45000"),
+                ("temp1_max", "80000"),
+                ("temp1_label", "Core 0"),
+                ("in0_input", "1200"),
+            ],
+        );
+
+        let doc = to_toml(std::slice::from_ref(&chip)).unwrap();
+        let table: ::toml::Table = doc.parse().unwrap();
+
+        let chip_table = table["dummy-virtual-0"].as_table().unwrap();
+        assert_eq!(chip_table["prefix"].as_str(), Some("dummy"));
+        assert_eq!(chip_table["bus"].as_str(), Some("Virtual"));
+
+        let temp1 = chip_table["temp1"].as_table().unwrap();
+        assert_eq!(temp1["label"].as_str(), Some("Core 0"));
+        assert_eq!(temp1["temp1_input"].as_float(), Some(45.0));
+        assert_eq!(temp1["temp1_max"].as_float(), Some(80.0));
+
+        let in0 = chip_table["in0"].as_table().unwrap();
+        assert_eq!(in0["in0_input"].as_float(), Some(1.2));
+    }
+
+    #[test]
+    fn to_toml_same_name_chips() {
+        let (_dir0, chip0) = fake_chip("acpitz", &[("temp1_input", "27800")]);
+        let (_dir1, chip1) = fake_chip("acpitz", &[("temp1_input", "29800")]);
+        let (_dir2, chip2) = fake_chip("dummy", &[("temp1_input", "45000")]);
+
+        let doc = to_toml(&[chip0, chip1, chip2]).unwrap();
+        let table: ::toml::Table = doc.parse().unwrap();
+        assert_eq!(table.len(), 3);
+        assert!(table.contains_key("dummy-virtual-0"));
+        let acpitz: Vec<&String> = table
+            .keys()
+            .filter(|key| key.starts_with("acpitz-virtual-0@hwmon-test-"))
+            .collect();
+        assert_eq!(acpitz.len(), 2);
+    }
+}
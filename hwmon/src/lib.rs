@@ -8,17 +8,34 @@ mod chip;
 mod context;
 mod error;
 mod feature;
+pub mod format;
+mod mock;
+mod owned;
 mod prefix;
 mod ratio;
 pub mod subfeature;
 mod sysfs;
+mod unit;
 
 #[cfg(feature = "sensorsconf")]
 mod parser;
 
+#[cfg(test)]
+mod testutil;
+
 pub use crate::bus::{Bus, BusType};
-pub use crate::chip::{read_sysfs_chips, Chip, FeatureIter};
+pub use crate::chip::{
+    read_sysfs_chips, read_sysfs_chips_filtered, read_sysfs_chips_strict, Chip, ChipReadings,
+    FeatureIter,
+};
 pub use crate::context::Context;
 pub use crate::error::Error;
-pub use crate::feature::{Feature, FeatureType, SubfeatureIter};
-pub use crate::subfeature::{Subfeature, SubfeatureType};
+pub use crate::feature::{Feature, FeatureReadings, FeatureType, SubfeatureIter, TempStatus};
+pub use crate::mock::MockFs;
+pub use crate::owned::{OwnedChip, OwnedFeature, OwnedSubfeature};
+#[cfg(feature = "sensorsconf")]
+pub use crate::parser::{Expr, Function, Operator};
+pub use crate::subfeature::{
+    CachedReader, EnergyCounter, PwmEnable, Subfeature, SubfeatureType, TempSensorKind,
+};
+pub use crate::unit::{Measurement, Unit};
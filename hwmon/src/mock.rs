@@ -0,0 +1,308 @@
+// SPDX-FileCopyrightText: 2026 Camille019
+// SPDX-License-Identifier: MPL-2.0
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::ffi::{OsStr, OsString};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::SystemTime;
+
+use crate::sysfs::FsProvider;
+
+/// Number of symbolic links followed by `canonicalize` before giving up, like Linux.
+const MAX_SYMLINKS: usize = 40;
+
+#[derive(Clone, Debug)]
+enum MockEntry {
+    File { contents: String, mode: u32 },
+    Dir,
+    Link(PathBuf),
+}
+
+/// An in-memory sysfs tree, to build a `Context` with `Context::with_mock` in tests
+/// and examples, without a real `/sys`.
+///
+/// Paths are absolute, like `/sys/class/hwmon/hwmon0/temp1_input`, and directories are
+/// created along with the files they contain. The chips are found and read like on a
+/// real sysfs tree, and the subfeature writes update the files of the mock. Symbolic
+/// links are only followed to resolve the path given to `Chip::subfeature_by_path`,
+/// `device/subsystem` must be added as a link next to the `device` one.
+///
+/// ```
+/// let mut mock = hwmon::MockFs::new();
+/// mock.file("/sys/class/hwmon/hwmon0/name", "dummy");
+/// mock.file("/sys/class/hwmon/hwmon0/temp1_input", "45000");
+/// mock.dir("/sys/class/i2c-adapter");
+///
+/// let context = hwmon::Context::with_mock(mock).unwrap();
+/// assert_eq!(context.chips().unwrap()[0].name(), "dummy-virtual-0");
+/// ```
+///
+/// The clones of a mock share the same tree, so a clone kept aside can add or remove
+/// files after the context is built, like a chip being plugged in.
+///
+/// The async reads always use the real file system.
+#[derive(Clone, Debug, Default)]
+pub struct MockFs {
+    entries: Rc<RefCell<BTreeMap<PathBuf, MockEntry>>>,
+}
+
+impl MockFs {
+    /// Create an empty tree.
+    pub fn new() -> MockFs {
+        MockFs::default()
+    }
+
+    /// Add a regular file, readable and writable by its owner.
+    pub fn file<P: AsRef<Path>>(&mut self, path: P, contents: &str) {
+        self.file_with_mode(path, contents, 0o644);
+    }
+
+    /// Add a regular file with the given permissions, like `0o444` for a read-only one.
+    pub fn file_with_mode<P: AsRef<Path>>(&mut self, path: P, contents: &str, mode: u32) {
+        let entry = MockEntry::File {
+            contents: contents.to_owned(),
+            mode: libc::S_IFREG | mode,
+        };
+        self.insert(path.as_ref(), entry);
+    }
+
+    /// Add an empty directory.
+    pub fn dir<P: AsRef<Path>>(&mut self, path: P) {
+        self.insert(path.as_ref(), MockEntry::Dir);
+    }
+
+    /// Add a symbolic link pointing to `target`.
+    pub fn symlink<P: AsRef<Path>, Q: AsRef<Path>>(&mut self, path: P, target: Q) {
+        self.insert(path.as_ref(), MockEntry::Link(target.as_ref().to_owned()));
+    }
+
+    /// Remove a file, a link or a directory along with its content.
+    pub fn remove<P: AsRef<Path>>(&mut self, path: P) {
+        let path = path.as_ref();
+        self.entries
+            .borrow_mut()
+            .retain(|entry, _| !entry.starts_with(path));
+    }
+
+    fn insert(&mut self, path: &Path, entry: MockEntry) {
+        let mut entries = self.entries.borrow_mut();
+        for ancestor in path.ancestors().skip(1) {
+            entries.entry(ancestor.to_owned()).or_insert(MockEntry::Dir);
+        }
+        entries.insert(path.to_owned(), entry);
+    }
+
+    fn entry(&self, path: &Path) -> io::Result<MockEntry> {
+        self.entries
+            .borrow()
+            .get(path)
+            .cloned()
+            .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+    }
+}
+
+impl FsProvider for MockFs {
+    fn read_file(&self, path: &Path) -> io::Result<String> {
+        match self.entry(path)? {
+            MockEntry::File { contents, mode } if mode & libc::S_IRUSR != 0 => {
+                Ok(contents.trim_end().to_owned())
+            }
+            MockEntry::File { .. } => Err(io::ErrorKind::PermissionDenied.into()),
+            _ => Err(io::ErrorKind::InvalidInput.into()),
+        }
+    }
+
+    fn write_file(&self, path: &Path, contents: &str) -> io::Result<()> {
+        match self.entries.borrow_mut().get_mut(path) {
+            Some(MockEntry::File {
+                contents: current,
+                mode,
+            }) if *mode & libc::S_IWUSR != 0 => {
+                *current = contents.to_owned();
+                Ok(())
+            }
+            Some(MockEntry::File { .. }) => Err(io::ErrorKind::PermissionDenied.into()),
+            Some(_) => Err(io::ErrorKind::InvalidInput.into()),
+            None => Err(io::ErrorKind::NotFound.into()),
+        }
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        match self.entry(path)? {
+            MockEntry::Dir => Ok(self
+                .entries
+                .borrow()
+                .keys()
+                .filter(|entry| entry.parent() == Some(path))
+                .cloned()
+                .collect()),
+            _ => Err(io::ErrorKind::InvalidInput.into()),
+        }
+    }
+
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        match self.entry(path)? {
+            MockEntry::Link(target) => Ok(target),
+            _ => Err(io::ErrorKind::InvalidInput.into()),
+        }
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        let mut resolved = PathBuf::from("/");
+        let mut pending: Vec<OsString> = path.iter().rev().map(OsStr::to_owned).collect();
+        let mut links = 0;
+        while let Some(name) = pending.pop() {
+            if name == "/" {
+                resolved = PathBuf::from("/");
+                continue;
+            }
+            if name == "." {
+                continue;
+            }
+            if name == ".." {
+                resolved.pop();
+                continue;
+            }
+            let candidate = resolved.join(&name);
+            match self.entry(&candidate)? {
+                MockEntry::Link(target) => {
+                    links += 1;
+                    if links > MAX_SYMLINKS {
+                        return Err(io::Error::from_raw_os_error(libc::ELOOP));
+                    }
+                    pending.extend(target.iter().rev().map(OsStr::to_owned));
+                }
+                _ => resolved = candidate,
+            }
+        }
+        Ok(resolved)
+    }
+
+    fn mode(&self, path: &Path) -> io::Result<u32> {
+        match self.entry(path)? {
+            MockEntry::File { mode, .. } => Ok(mode),
+            MockEntry::Dir => Ok(libc::S_IFDIR | 0o755),
+            MockEntry::Link(_) => Ok(libc::S_IFLNK | 0o777),
+        }
+    }
+
+    fn modified(&self, _path: &Path) -> io::Result<SystemTime> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        matches!(self.entry(path), Ok(MockEntry::Dir))
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        matches!(self.entry(path), Ok(MockEntry::File { .. }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::Context;
+    use crate::feature::FeatureType;
+    use crate::subfeature::{SubfeatureType, Temperature};
+
+    #[test]
+    fn mock_fs_scan_chip() {
+        let mut mock = MockFs::new();
+        mock.dir("/sys/class/i2c-adapter");
+        mock.file("/sys/class/hwmon/hwmon0/name", "dummy\n");
+        mock.file("/sys/class/hwmon/hwmon0/temp1_input", "45000\n");
+        mock.file("/sys/class/hwmon/hwmon0/temp1_max", "80000\n");
+        mock.file("/sys/class/hwmon/hwmon0/temp1_label", "CPU\n");
+        mock.file_with_mode("/sys/class/hwmon/hwmon0/fan1_input", "1250\n", 0o444);
+
+        let context = Context::with_mock(mock).unwrap();
+        let chips = context.chips().unwrap();
+        assert_eq!(chips.len(), 1);
+        let chip = &chips[0];
+        assert_eq!(chip.name(), "dummy-virtual-0");
+        assert_eq!(chip.features_iter().count(), 2);
+
+        let temp = chip.feature(FeatureType::Temperature, 1).unwrap();
+        assert_eq!(temp.label(), "CPU");
+        assert_eq!(temp.input_value(), Some(45.0));
+        let max = temp
+            .subfeature(SubfeatureType::Temperature(Temperature::Max))
+            .unwrap();
+        max.write_value(75.0).unwrap();
+        assert_eq!(max.read_raw_string().unwrap(), "75000");
+
+        let fan = chip.feature(FeatureType::Fan, 1).unwrap();
+        assert_eq!(fan.input_value(), Some(1250.0));
+        assert!(fan.writable_subfeatures().next().is_none());
+    }
+
+    #[test]
+    fn mock_fs_shared_clone() {
+        let mut mock = MockFs::new();
+        mock.dir("/sys/class/i2c-adapter");
+        mock.dir("/sys/class/hwmon");
+        let context = Context::with_mock(mock.clone()).unwrap();
+        assert!(crate::read_sysfs_chips(&context).unwrap().is_empty());
+
+        mock.file("/sys/class/hwmon/hwmon0/name", "dummy");
+        mock.file("/sys/class/hwmon/hwmon0/temp1_input", "45000");
+        assert_eq!(crate::read_sysfs_chips(&context).unwrap().len(), 1);
+
+        mock.remove("/sys/class/hwmon/hwmon0");
+        assert!(crate::read_sysfs_chips(&context).unwrap().is_empty());
+    }
+
+    #[test]
+    fn mock_fs_canonicalize() {
+        let mut mock = MockFs::new();
+        mock.file("/sys/class/hwmon/hwmon0/temp1_input", "45000");
+        mock.symlink(
+            "/sys/class/hwmon/hwmon0/device",
+            "../../../devices/platform/dummy",
+        );
+        mock.symlink(
+            "/sys/devices/platform/dummy/hwmon/hwmon0",
+            "/sys/class/hwmon/hwmon0",
+        );
+        mock.symlink("/sys/loop", "/sys/loop");
+
+        assert_eq!(
+            mock.canonicalize(Path::new(
+                "/sys/class/hwmon/hwmon0/device/hwmon/hwmon0/./temp1_input"
+            ))
+            .unwrap(),
+            Path::new("/sys/class/hwmon/hwmon0/temp1_input")
+        );
+        assert_eq!(
+            mock.canonicalize(Path::new("/sys/class/hwmon/hwmon0/device/.."))
+                .unwrap(),
+            Path::new("/sys/devices/platform")
+        );
+        assert_eq!(
+            mock.canonicalize(Path::new("/sys/class/hwmon/hwmon1"))
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::NotFound
+        );
+        assert_eq!(
+            mock.canonicalize(Path::new("/sys/loop"))
+                .unwrap_err()
+                .raw_os_error(),
+            Some(libc::ELOOP)
+        );
+    }
+
+    #[test]
+    fn mock_fs_unmounted() {
+        let mut mock = MockFs::new();
+        mock.dir("/sys");
+        assert!(matches!(
+            Context::with_mock(mock),
+            Err(crate::Error::SysfsUnavailable(_))
+        ));
+    }
+}
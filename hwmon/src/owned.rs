@@ -0,0 +1,123 @@
+// SPDX-FileCopyrightText: 2026 Camille019
+// SPDX-License-Identifier: MPL-2.0
+
+use std::path::PathBuf;
+
+use crate::bus::BusType;
+use crate::chip::Chip;
+use crate::feature::{Feature, FeatureType};
+use crate::subfeature::{Subfeature, SubfeatureType};
+
+/// A chip detached from its `Context`, see [`Chip::into_owned`].
+///
+/// It only holds plain data, so unlike `Chip` it is `Send` and `Sync` and can be handed
+/// over to a worker thread.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OwnedChip {
+    pub name: String,
+    pub prefix: String,
+    pub path: PathBuf,
+    pub bus_type: BusType,
+    pub bus_number: i32,
+    pub address: u32,
+    pub features: Vec<OwnedFeature>,
+}
+
+/// A feature of an [`OwnedChip`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct OwnedFeature {
+    pub name: String,
+    pub label: String,
+    pub feature_type: FeatureType,
+    pub number: u32,
+    pub subfeatures: Vec<OwnedSubfeature>,
+}
+
+/// A subfeature of an [`OwnedFeature`].
+///
+/// The sysfs file can still be read from `path`, but the compute statements of the
+/// configuration file are not applied to it anymore.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OwnedSubfeature {
+    pub name: String,
+    pub path: PathBuf,
+    pub subfeature_type: SubfeatureType,
+    pub is_readable: bool,
+    pub is_writable: bool,
+}
+
+impl OwnedChip {
+    pub(crate) fn new(chip: &Chip) -> OwnedChip {
+        OwnedChip {
+            name: chip.name(),
+            prefix: chip.prefix().to_owned(),
+            path: chip.path().to_owned(),
+            bus_type: chip.bus().get_type(),
+            bus_number: chip.bus().number(),
+            address: chip.address(),
+            features: chip.features_iter().map(OwnedFeature::new).collect(),
+        }
+    }
+}
+
+impl OwnedFeature {
+    fn new(feature: &Feature) -> OwnedFeature {
+        OwnedFeature {
+            name: feature.name().to_owned(),
+            label: feature.label(),
+            feature_type: feature.get_type(),
+            number: feature.number(),
+            subfeatures: feature
+                .subfeatures_iter()
+                .map(OwnedSubfeature::new)
+                .collect(),
+        }
+    }
+}
+
+impl OwnedSubfeature {
+    fn new(subfeature: &Subfeature) -> OwnedSubfeature {
+        OwnedSubfeature {
+            name: subfeature.name().to_owned(),
+            path: subfeature.path().to_owned(),
+            subfeature_type: subfeature.get_type(),
+            is_readable: subfeature.is_readable(),
+            is_writable: subfeature.is_writable(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::subfeature::Temperature;
+    use crate::sysfs::sysfs_read_file;
+    use crate::testutil::fake_chip;
+
+    #[test]
+    fn owned_chip_send_to_thread() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<OwnedChip>();
+
+        let (_dir, chip) = fake_chip(
+            "dummy",
+            &[("temp1_input", "45000"), ("temp1_label", "Core 0")],
+        );
+        let owned = chip.into_owned();
+
+        let handle = std::thread::spawn(move || {
+            assert_eq!(owned.name, "dummy-virtual-0");
+            assert_eq!(owned.bus_type, BusType::Virtual);
+            let temp1 = &owned.features[0];
+            assert_eq!(temp1.label, "Core 0");
+            assert_eq!(temp1.feature_type, FeatureType::Temperature);
+            let input = &temp1.subfeatures[0];
+            assert_eq!(
+                input.subfeature_type,
+                SubfeatureType::Temperature(Temperature::Input)
+            );
+            sysfs_read_file(&input.path).unwrap()
+        });
+        assert_eq!(handle.join().unwrap(), "45000");
+    }
+}
@@ -1,6 +1,7 @@
 // SPDX-FileCopyrightText: 2018 Camille019
 // SPDX-License-Identifier: MPL-2.0
 
+use std::fmt;
 use std::fs;
 use std::path::Path;
 use std::sync::LazyLock;
@@ -15,15 +16,29 @@ use crate::error::Error;
 #[grammar = "conf.pest"]
 pub(crate) struct SensorsConfParser;
 
-#[derive(Debug, PartialEq)]
-enum Operator {
+/// Binary operator of a compute expression.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Operator {
+    /// `+`
     Add,
+    /// `-`
     Sub,
+    /// `*`
     Multiply,
+    /// `/`
     Divide,
 }
 
 impl Operator {
+    /// Binding power of the operator, the operators of a same level are left
+    /// associative.
+    fn precedence(&self) -> u8 {
+        match self {
+            Operator::Add | Operator::Sub => 1,
+            Operator::Multiply | Operator::Divide => 2,
+        }
+    }
+
     fn eval(&self, left: f32, right: f32) -> f32 {
         match self {
             Operator::Add => left + right,
@@ -34,10 +49,26 @@ impl Operator {
     }
 }
 
-#[derive(Debug, PartialEq)]
-enum Function {
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let symbol = match self {
+            Operator::Add => "+",
+            Operator::Sub => "-",
+            Operator::Multiply => "*",
+            Operator::Divide => "/",
+        };
+        f.write_str(symbol)
+    }
+}
+
+/// Unary function of a compute expression.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Function {
+    /// `-`, the opposite of the operand.
     Inv,
+    /// `^`, the exponential of the operand.
     Exp,
+    /// `` ` ``, the natural logarithm of the operand.
     Ln,
 }
 
@@ -51,22 +82,82 @@ impl Function {
     }
 }
 
-#[derive(Debug, Default, PartialEq)]
-enum Expr {
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let symbol = match self {
+            Function::Inv => "-",
+            Function::Exp => "^",
+            Function::Ln => "`",
+        };
+        f.write_str(symbol)
+    }
+}
+
+/// Parsed expression of a `compute` statement of the configuration file.
+///
+/// The `Display` implementation renders the infix form, with only the parentheses
+/// needed to parse back the same expression.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Expr {
+    /// A function applied to an expression.
     Fn(Function, Box<Expr>),
+    /// An operator applied to two expressions.
     Op(Operator, Box<Expr>, Box<Expr>),
+    /// A number.
     Literal(f32),
+    /// `@`, the value being converted.
     #[default]
     Raw,
 }
 
-impl Expr {
-    fn eval(&self, raw: f32) -> f32 {
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Expr::Fn(ref inner, ref expr) => inner.eval(expr.eval(raw)),
-            Expr::Op(ref inner, ref left, ref right) => inner.eval(left.eval(raw), right.eval(raw)),
+            Expr::Fn(function, expr) => match **expr {
+                Expr::Op(..) => write!(f, "{}({})", function, expr),
+                _ => write!(f, "{}{}", function, expr),
+            },
+            Expr::Op(operator, left, right) => {
+                match **left {
+                    Expr::Op(ref inner, ..) if inner.precedence() < operator.precedence() => {
+                        write!(f, "({})", left)?
+                    }
+                    _ => write!(f, "{}", left)?,
+                }
+                write!(f, " {} ", operator)?;
+                match **right {
+                    Expr::Op(ref inner, ..) if inner.precedence() <= operator.precedence() => {
+                        write!(f, "({})", right)
+                    }
+                    _ => write!(f, "{}", right),
+                }
+            }
+            Expr::Literal(value) => write!(f, "{}", value),
+            Expr::Raw => f.write_str("@"),
+        }
+    }
+}
+
+impl Expr {
+    /// Evaluate the expression with `raw` as the `@` operand.
+    ///
+    /// Return `Error::InvalidValue` as soon as an intermediate result is not finite
+    /// (e.g. `ln` of a negative number, division by zero or overflow), instead of
+    /// propagating a `NaN` or infinite value.
+    fn eval(&self, raw: f32) -> Result<f32, Error> {
+        let value = match self {
+            Expr::Fn(ref inner, ref expr) => inner.eval(expr.eval(raw)?),
+            Expr::Op(ref inner, ref left, ref right) => {
+                inner.eval(left.eval(raw)?, right.eval(raw)?)
+            }
             Expr::Literal(inner) => *inner,
             Expr::Raw => raw,
+        };
+
+        if value.is_finite() {
+            Ok(value)
+        } else {
+            Err(Error::InvalidValue)
         }
     }
 }
@@ -83,6 +174,87 @@ pub(crate) struct CfgFile {
     chips: Vec<StmtChip>,
 }
 
+impl CfgFile {
+    /// Merge the statements of a configuration read after this one.
+    ///
+    /// For a given chip name, feature name and kind of statement, the statements of
+    /// `other` take precedence over the current ones. The chip statements are kept
+    /// in order and looked up last-first, so the other statements still apply.
+    /// Bus statements accumulate.
+    pub(crate) fn merge(&mut self, other: CfgFile) {
+        self.buses.extend(other.buses);
+        self.chips.extend(other.chips);
+    }
+
+    /// Iterate over the chip statements matching `chip_name`, the last one first,
+    /// as later statements take precedence over earlier ones.
+    fn chips_matching<'a, 'b>(
+        &'a self,
+        chip_name: &'b str,
+    ) -> impl Iterator<Item = &'a StmtChip> + 'b
+    where
+        'a: 'b,
+    {
+        self.chips
+            .iter()
+            .rev()
+            .filter(move |chip| chip.names.iter().any(|name| glob_match(name, chip_name)))
+    }
+
+    /// Return the adapter name given to the bus `number`, like `i2c-0`, if any.
+    pub(crate) fn bus_adapter(&self, number: &str) -> Option<&str> {
+        self.buses
+            .iter()
+            .rev()
+            .find(|bus| bus.number == number)
+            .map(|bus| bus.adapter.as_str())
+    }
+
+    /// Return the label of the feature `feature_name` of the chip `chip_name`, if any.
+    pub(crate) fn label(&self, chip_name: &str, feature_name: &str) -> Option<&str> {
+        self.chips_matching(chip_name).find_map(|chip| {
+            chip.labels
+                .iter()
+                .rev()
+                .find(|label| label.name == feature_name)
+                .map(|label| label.value.as_str())
+        })
+    }
+
+    /// Iterate over all the compute statements, along with the names of their chip
+    /// statement.
+    pub(crate) fn computes(&self) -> impl Iterator<Item = (&[String], &StmtCompute)> {
+        self.chips.iter().flat_map(|chip| {
+            chip.computes
+                .iter()
+                .map(move |compute| (chip.names.as_slice(), compute))
+        })
+    }
+
+    /// Return the compute statement of the feature `feature_name` of the chip `chip_name`, if any.
+    pub(crate) fn compute(&self, chip_name: &str, feature_name: &str) -> Option<&StmtCompute> {
+        self.chips_matching(chip_name).find_map(|chip| {
+            chip.computes
+                .iter()
+                .rev()
+                .find(|compute| compute.name == feature_name)
+        })
+    }
+}
+
+/// Match `name` against `pattern`, where `*` matches any sequence of characters.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == name,
+        Some((head, tail)) => match name.strip_prefix(head) {
+            None => false,
+            Some(rest) => (0..=rest.len())
+                .filter(|&i| rest.is_char_boundary(i))
+                .any(|i| glob_match(tail, &rest[i..])),
+        },
+    }
+}
+
 #[derive(Debug, Default, PartialEq)]
 struct StmtBus {
     number: String,
@@ -110,12 +282,54 @@ struct StmtIgnore {
 }
 
 #[derive(Debug, Default, PartialEq)]
-struct StmtCompute {
+pub(crate) struct StmtCompute {
     name: String,
     from_proc: Expr,
     to_proc: Expr,
 }
 
+impl StmtCompute {
+    /// Name of the feature the statement applies to.
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Check that `to_proc` is the inverse of `from_proc`: `sample` converted by
+    /// `from_proc` then `to_proc` must give back `sample`, within the precision of
+    /// `f32`. Return `false` as well if one of the conversions fails.
+    pub(crate) fn check_invertible(&self, sample: f32) -> bool {
+        let back = self
+            .from_proc
+            .eval(sample)
+            .and_then(|value| self.to_proc.eval(value));
+        match back {
+            Ok(back) => (back - sample).abs() <= 1e-4 * sample.abs().max(1.0),
+            Err(_) => false,
+        }
+    }
+
+    /// Convert a value read from sysfs.
+    pub(crate) fn eval_from_proc(&self, value: f64) -> Result<f64, Error> {
+        self.from_proc.eval(value as f32).map(f64::from)
+    }
+
+    /// Convert a value to be written to sysfs.
+    pub(crate) fn eval_to_proc(&self, value: f64) -> Result<f64, Error> {
+        self.to_proc.eval(value as f32).map(f64::from)
+    }
+
+    /// Expression converting the values read from sysfs.
+    pub(crate) fn expr_from_proc(&self) -> &Expr {
+        &self.from_proc
+    }
+
+    /// Render the statement as written after the feature name in a configuration file,
+    /// like `@ * 2, @ / 2`.
+    pub(crate) fn statement(&self) -> String {
+        format!("{}, {}", self.from_proc, self.to_proc)
+    }
+}
+
 #[derive(Debug, Default, PartialEq)]
 struct StmtSet {
     name: String,
@@ -362,11 +576,27 @@ fn parse_pfile(pfile: Pair<Rule>) -> CfgFile {
     cfg
 }
 
+/// Parse the expressions of a compute statement, without the `compute` keyword and
+/// the feature name, like `@*2, @/2`.
+pub(crate) fn parse_compute_str(data: &str) -> Result<StmtCompute, Error> {
+    let mut pexprs = SensorsConfParser::parse(Rule::compute_exprs, data)
+        .map_err(|err| Error::ParseConfig(err.to_string()))?
+        .next()
+        .ok_or_else(|| Error::ParseConfig(String::from("empty parse tree")))?
+        .into_inner();
+
+    Ok(StmtCompute {
+        name: String::new(),
+        from_proc: parse_pexpr(pexprs.next().unwrap()),
+        to_proc: parse_pexpr(pexprs.next().unwrap()),
+    })
+}
+
 pub(crate) fn parse_configuration_str(data: &str) -> Result<CfgFile, Error> {
     let root = SensorsConfParser::parse(Rule::file, data)
-        .unwrap()
+        .map_err(|err| Error::ParseConfig(err.to_string()))?
         .next()
-        .unwrap();
+        .ok_or_else(|| Error::ParseConfig(String::from("empty parse tree")))?;
 
     let cfg = parse_pfile(root);
 
@@ -374,7 +604,7 @@ pub(crate) fn parse_configuration_str(data: &str) -> Result<CfgFile, Error> {
 }
 
 pub(crate) fn parse_configuration_file<P: AsRef<Path>>(path: P) -> Result<CfgFile, Error> {
-    let file = fs::read_to_string(path).ok().unwrap();
+    let file = fs::read_to_string(path)?;
 
     parse_configuration_str(&file)
 }
@@ -780,6 +1010,36 @@ chip "blah-*"
         assert_eq!(conf == expected, false);
     }
 
+    #[test]
+    fn eval_expr_ln_negative() {
+        let expr = Expr::Fn(Function::Ln, Box::new(Expr::Raw));
+        assert!(matches!(expr.eval(-1.0), Err(Error::InvalidValue)));
+        assert!(matches!(expr.eval(0.0), Err(Error::InvalidValue)));
+        assert!(expr.eval(1.0).is_ok());
+    }
+
+    #[test]
+    fn eval_expr_divide_by_zero() {
+        let expr = Expr::Op(
+            Operator::Divide,
+            Box::new(Expr::Raw),
+            Box::new(Expr::Literal(0.0)),
+        );
+        assert!(matches!(expr.eval(1.0), Err(Error::InvalidValue)));
+        assert!(matches!(expr.eval(0.0), Err(Error::InvalidValue)));
+    }
+
+    #[test]
+    fn eval_expr_overflow() {
+        let expr = Expr::Op(
+            Operator::Multiply,
+            Box::new(Expr::Raw),
+            Box::new(Expr::Literal(1e30)),
+        );
+        assert!(matches!(expr.eval(1e30), Err(Error::InvalidValue)));
+        assert_eq!(expr.eval(2.0).unwrap(), 2e30);
+    }
+
     #[test]
     fn parse_conf_str_compute() {
         let cfg_str = r#"
@@ -789,6 +1049,177 @@ chip "lm78-*"
     compute in2 @*(1+120/56) - 4.096*120/56, `(@ + 4.096*120/56)/(1+120/56)
     compute in3 @*(1+120/56) - 4.096*120/56, ^(@ + 4.096*120/56)/(1+120/56)
 "#;
-        assert_eq!(parse_configuration_str(cfg_str).is_ok(), true);
+        let conf = parse_configuration_str(cfg_str).unwrap();
+
+        let ratio = 1.0 + 120.0 / 56.0;
+        let offset = 4.096 * 120.0 / 56.0;
+        let raw = 2.0f32;
+        for name in ["in1", "in2", "in3"] {
+            let compute = conf.compute("lm78-isa-0290", name).unwrap();
+            assert_close(compute.from_proc.eval(raw).unwrap(), raw * ratio - offset);
+        }
+        let to_proc = |name| {
+            conf.compute("lm78-isa-0290", name)
+                .unwrap()
+                .to_proc
+                .eval(raw)
+                .unwrap()
+        };
+        assert_close(to_proc("in1"), -(raw + offset) / ratio);
+        assert_close(to_proc("in2"), (raw + offset).ln() / ratio);
+        assert_close(to_proc("in3"), (raw + offset).exp() / ratio);
+    }
+
+    /// Parse a single compute statement and return its `from_proc` and `to_proc`
+    /// expressions.
+    fn parse_compute_exprs(compute: &str) -> (Expr, Expr) {
+        let cfg_str = format!("chip \"lm78-*\"\n    compute in0 {}\n", compute);
+        let mut conf = parse_configuration_str(&cfg_str).unwrap();
+        let compute = conf.chips.remove(0).computes.remove(0);
+        (compute.from_proc, compute.to_proc)
+    }
+
+    #[test]
+    fn compute_check_invertible() {
+        let compute =
+            parse_compute_str("@*(1+120/56) - 4.096*120/56, (@ + 4.096*120/56)/(1+120/56)")
+                .unwrap();
+        assert!(compute.check_invertible(1.0));
+        assert!(compute.check_invertible(-12.5));
+
+        let compute = parse_compute_str("`@, ^@").unwrap();
+        assert!(compute.check_invertible(3.3));
+        // ln of a negative value
+        assert!(!compute.check_invertible(-1.0));
+
+        let compute = parse_compute_str("@*2, @*2").unwrap();
+        assert!(!compute.check_invertible(1.0));
+    }
+
+    #[test]
+    fn display_expr_parentheses() {
+        for compute in [
+            "@ * 2 + 1, @",
+            "(@ + 1) * 2, @",
+            "@ - (@ - 1), @",
+            "-(@ + 4.096) / 2.5, @",
+            "^@ / `@ - 0.5, @",
+        ] {
+            let (from_proc, _) = parse_compute_exprs(compute);
+            let rendered = from_proc.to_string();
+            assert_eq!(format!("{}, @", rendered), compute);
+            assert_eq!(parse_compute_exprs(compute).0, from_proc);
+        }
+    }
+
+    fn assert_close(left: f32, right: f32) {
+        assert!((left - right).abs() < 1e-5, "{} != {}", left, right);
+    }
+
+    #[test]
+    fn eval_compute_lm78_nested_raw() {
+        let (from_proc, to_proc) =
+            parse_compute_exprs("@*(1+120/56) - 4.096*120/56, -(@ + 4.096*120/56)/(1+120/56)");
+
+        let ratio = 1.0 + 120.0 / 56.0;
+        let offset = 4.096 * 120.0 / 56.0;
+        assert_close(from_proc.eval(2.0).unwrap(), 2.0 * ratio - offset);
+        assert_close(to_proc.eval(2.0).unwrap(), -(2.0 + offset) / ratio);
+        assert_close(to_proc.eval(-offset).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn eval_compute_precedence() {
+        let eval = |expr: &str, raw: f32| {
+            let (from_proc, _) = parse_compute_exprs(&format!("{}, @", expr));
+            from_proc.eval(raw).unwrap()
+        };
+
+        assert_close(eval("@-1-1", 5.0), 3.0);
+        assert_close(eval("@/2/2", 8.0), 2.0);
+        assert_close(eval("2*@+4*5", 3.0), 26.0);
+        assert_close(eval("@-2*3", 10.0), 4.0);
+        assert_close(eval("(@-2)*3", 10.0), 24.0);
+        assert_close(eval("-@*2", 3.0), -6.0);
+        assert_close(eval("-@+2", 3.0), -1.0);
+        assert_close(eval("^@+1", 0.0), 2.0);
+        assert_close(eval("`@*2", 1.0), 0.0);
+        assert_close(eval("^(@+1)", 0.0), std::f32::consts::E);
+    }
+
+    #[test]
+    fn glob_match_chip_names() {
+        assert!(glob_match("lm78-*", "lm78-isa-0290"));
+        assert!(glob_match("*-isa-0290", "lm78-isa-0290"));
+        assert!(glob_match("it87-*-*", "it87-isa-0290"));
+        assert!(glob_match("*", "coretemp-isa-0000"));
+        assert!(!glob_match("lm78-*", "lm75-i2c-0-48"));
+        assert!(!glob_match("lm78-isa-0290", "lm78-isa-0291"));
+    }
+
+    #[test]
+    fn cfg_file_label_last_statement_wins() {
+        let cfg_str = r#"
+chip "lm78-*"
+    label in0 "Vcore"
+    label in1 "V3.3"
+
+chip "lm78-isa-*"
+    label in0 "CPU"
+"#;
+        let conf = parse_configuration_str(cfg_str).unwrap();
+        assert_eq!(conf.label("lm78-isa-0290", "in0"), Some("CPU"));
+        assert_eq!(conf.label("lm78-isa-0290", "in1"), Some("V3.3"));
+        assert_eq!(conf.label("lm78-i2c-0-2d", "in0"), Some("Vcore"));
+        assert_eq!(conf.label("lm75-i2c-0-48", "in0"), None);
+    }
+
+    #[test]
+    fn cfg_file_compute() {
+        let cfg_str = r#"
+chip "lm78-*"
+    compute in0 @*2, @/2
+"#;
+        let conf = parse_configuration_str(cfg_str).unwrap();
+        let compute = conf.compute("lm78-isa-0290", "in0").unwrap();
+        assert_eq!(compute.eval_from_proc(1.5).unwrap(), 3.0);
+        assert_eq!(compute.eval_to_proc(3.0).unwrap(), 1.5);
+        assert!(conf.compute("lm78-isa-0290", "in1").is_none());
+    }
+
+    #[test]
+    fn parse_conf_file_missing() {
+        assert!(matches!(
+            parse_configuration_file("/nonexistent/sensors.conf"),
+            Err(Error::Io(_))
+        ));
+    }
+
+    #[test]
+    fn cfg_file_merge_last_wins() {
+        let mut conf = parse_configuration_str(
+            r#"
+bus "i2c-0" "SMBus I801 adapter at 5000"
+
+chip "lm78-*"
+    label in0 "Vcore"
+    label in1 "V3.3"
+"#,
+        )
+        .unwrap();
+        let other = parse_configuration_str(
+            r#"
+bus "i2c-1" "NVIDIA i2c adapter"
+
+chip "lm78-*"
+    label in0 "CPU"
+"#,
+        )
+        .unwrap();
+
+        conf.merge(other);
+        assert_eq!(conf.label("lm78-isa-0290", "in0"), Some("CPU"));
+        assert_eq!(conf.label("lm78-isa-0290", "in1"), Some("V3.3"));
+        assert_eq!(conf.buses.len(), 2);
     }
 }
@@ -34,7 +34,7 @@ pub mod si {
 }
 
 pub mod iec {
-    #![allow(non_upper_case_globals, dead_code)]
+    #![allow(non_upper_case_globals, dead_code, unused_imports)]
     pub use crate::prefix::Unity;
     use crate::ratio::Ratio;
 
@@ -1,44 +1,69 @@
 // SPDX-FileCopyrightText: 2018 Camille019
 // SPDX-License-Identifier: MPL-2.0
 
+use std::cell::Cell;
 use std::collections::HashMap;
 use std::ffi::OsStr;
-use std::fs::OpenOptions;
-use std::io::Write;
-use std::os::linux::fs::MetadataExt;
 use std::path::{Path, PathBuf};
+#[cfg(feature = "sensorsconf")]
+use std::rc::Rc;
 use std::str::FromStr;
 use std::sync::LazyLock;
+use std::time::{Duration, Instant, SystemTime};
 
+#[cfg(feature = "sensorsconf")]
+use crate::context::FeatureConfig;
 use crate::error::*;
 use crate::feature::FeatureType;
+#[cfg(feature = "sensorsconf")]
+use crate::parser::{self, Expr, StmtCompute};
 use crate::prefix::si::*;
 use crate::ratio::Ratio;
 use crate::sysfs::*;
+use crate::unit::{Measurement, Unit};
 
 type SubfeatureTypeMap = HashMap<&'static str, SubfeatureType>;
 
 macro_rules! make_subfeatures {
-    (feature: $Feature:ident, map: $MAP_NAME:ident, variants: [ $($Variant:ident { $pattern:expr, $ratio:ident, $alarm:expr}),* $(,)* ]) => {
+    (feature: $Feature:ident, map: $MAP_NAME:ident, variants: [ $($(#[$attr:meta])* $Variant:ident { $pattern:expr, $ratio:ident, $unit:ident, $alarm:expr}),* $(,)* ]) => {
         #[allow(non_camel_case_types)]
         #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
         pub enum $Feature {
-            $($Variant),*
+            $($(#[$attr])* $Variant),*
         }
 
         impl $Feature {
+            /// All the subfeature variants, in declaration order.
+            pub(crate) const SUBFEATURE_TYPES: &'static [SubfeatureType] =
+                &[$(SubfeatureType::$Feature($Feature::$Variant)),*];
+
             fn ratio(self) -> &'static Ratio<u64> {
                 match self {
                     $($Feature::$Variant => &$ratio,)*
                 }
             }
 
+            /// Return the unit of the subfeature variant value.
+            pub fn unit(self) -> Unit {
+                match self {
+                    $($Feature::$Variant => Unit::$unit,)*
+                }
+            }
+
             /// Return `true` if the subfeature variant is an alarm.
             pub fn is_alarm(self) -> bool {
                 match self {
                     $($Feature::$Variant => $alarm,)*
                 }
             }
+
+            /// Return the suffix of the sysfs attribute name of the subfeature variant,
+            /// like `"crit_alarm"`.
+            fn suffix(self) -> &'static str {
+                match self {
+                    $($Feature::$Variant => $pattern,)*
+                }
+            }
         }
 
         static $MAP_NAME: LazyLock<SubfeatureTypeMap> = LazyLock::new(|| {
@@ -54,19 +79,19 @@ make_subfeatures! {
     feature: Fan,
     map: FAN_MAP,
     variants: [
-        Input { "input", Unity, false },
-        Min { "min", Unity, false },
-        Max { "max", Unity, false },
-        Div { "div", Unity, false },
-        Pulses { "pulses", Unity, false },
-        Target { "target", Unity, false },
-        Enable { "enable", Unity, false },
+        Input { "input", Unity, Rpm, false },
+        Min { "min", Unity, Rpm, false },
+        Max { "max", Unity, Rpm, false },
+        Div { "div", Unity, Dimensionless, false },
+        Pulses { "pulses", Unity, Dimensionless, false },
+        Target { "target", Unity, Rpm, false },
+        Enable { "enable", Unity, Dimensionless, false },
         // Alarms
-        Alarm { "alarm", Unity, true },
-        Min_Alarm { "min_alarm", Unity, true },
-        Max_Alarm { "max_alarm", Unity, true },
-        Fault { "fault", Unity, false },
-        Beep { "beep", Unity, false },
+        Alarm { "alarm", Unity, Dimensionless, true },
+        Min_Alarm { "min_alarm", Unity, Dimensionless, true },
+        Max_Alarm { "max_alarm", Unity, Dimensionless, true },
+        Fault { "fault", Unity, Dimensionless, false },
+        Beep { "beep", Unity, Dimensionless, false },
     ]
 }
 
@@ -74,10 +99,10 @@ make_subfeatures! {
     feature: Pwm,
     map: PWM_MAP,
     variants: [
-        Pwm { "", Unity, false },
-        Enable { "enable", Unity, false },
-        Mode { "mode", Unity, false },
-        Freq { "freq", Unity, false },
+        Pwm { "", Unity, Dimensionless, false },
+        Enable { "enable", Unity, Dimensionless, false },
+        Mode { "mode", Unity, Dimensionless, false },
+        Freq { "freq", Unity, Hertz, false },
     ]
 }
 
@@ -85,31 +110,36 @@ make_subfeatures! {
     feature: Temperature,
     map: TEMPERATURE_MAP,
     variants: [
-        Input { "input", Milli, false },
-        Max { "max", Milli, false },
-        Max_Hyst { "max_hyst", Milli, false },
-        Min { "min", Milli, false },
-        Min_Hyst { "min_hyst", Milli, false },
-        Crit_Max { "crit", Milli, false },
-        Crit_Max_Hyst { "crit_hyst", Milli, false },
-        Crit_Min { "lcrit", Milli, false },
-        Crit_Min_Hyst { "lcrit_hyst", Milli, false },
-        Emergency { "emergency", Milli, false },
-        Emergency_Hyst { "emergency_hyst", Milli, false },
-        Lowest { "lowest", Milli, false },
-        Highest { "highest", Milli, false },
-        Offset { "offset", Milli, false },
-        Type { "type", Unity, false },
-        Enable { "enable", Unity, false },
+        Input { "input", Milli, Celsius, false },
+        Max { "max", Milli, Celsius, false },
+        Max_Hyst { "max_hyst", Milli, Celsius, false },
+        Min { "min", Milli, Celsius, false },
+        Min_Hyst { "min_hyst", Milli, Celsius, false },
+        Crit_Max { "crit", Milli, Celsius, false },
+        Crit_Max_Hyst { "crit_hyst", Milli, Celsius, false },
+        Crit_Min { "lcrit", Milli, Celsius, false },
+        Crit_Min_Hyst { "lcrit_hyst", Milli, Celsius, false },
+        Emergency { "emergency", Milli, Celsius, false },
+        Emergency_Hyst { "emergency_hyst", Milli, Celsius, false },
+        Lowest { "lowest", Milli, Celsius, false },
+        Highest { "highest", Milli, Celsius, false },
+        Offset { "offset", Milli, Celsius, false },
+        Reset_History { "reset_history", Unity, Dimensionless, false },
+        Type { "type", Unity, Dimensionless, false },
+        Enable { "enable", Unity, Dimensionless, false },
         // Alarms
-        Alarm { "alarm", Unity, true },
-        Max_Alarm { "max_alarm", Unity, true },
-        Min_Alarm { "min_alarm", Unity, true },
-        Emergency_Alarm { "emergency_alarm", Unity, true },
-        Crit_Max_Alarm { "crit_alarm", Unity, true },
-        Crit_Min_Alarm { "lcrit_alarm", Unity, true },
-        Fault { "fault", Unity, false },
-        Beep { "beep", Unity, false },
+        Alarm { "alarm", Unity, Dimensionless, true },
+        Max_Alarm { "max_alarm", Unity, Dimensionless, true },
+        Min_Alarm { "min_alarm", Unity, Dimensionless, true },
+        Emergency_Alarm { "emergency_alarm", Unity, Dimensionless, true },
+        Crit_Max_Alarm { "crit_alarm", Unity, Dimensionless, true },
+        Crit_Min_Alarm { "lcrit_alarm", Unity, Dimensionless, true },
+        Fault { "fault", Unity, Dimensionless, false },
+        Beep { "beep", Unity, Dimensionless, false },
+        Max_Beep { "max_beep", Unity, Dimensionless, false },
+        Min_Beep { "min_beep", Unity, Dimensionless, false },
+        Crit_Max_Beep { "crit_beep", Unity, Dimensionless, false },
+        Crit_Min_Beep { "lcrit_beep", Unity, Dimensionless, false },
     ]
 }
 
@@ -117,22 +147,29 @@ make_subfeatures! {
     feature: Voltage,
     map: VOLTAGE_MAP,
     variants: [
-        Input { "input", Milli, false },
-        Max { "max", Milli, false },
-        Min { "min", Milli, false },
-        Crit_Max { "crit", Milli, false },
-        Crit_Min { "lcrit", Milli, false },
-        Average { "average", Milli, false },
-        Highest { "highest", Milli, false },
-        Lowest { "lowest", Milli, false },
-        Enable { "enable", Unity, false },
+        Input { "input", Milli, Volt, false },
+        Max { "max", Milli, Volt, false },
+        Min { "min", Milli, Volt, false },
+        Crit_Max { "crit", Milli, Volt, false },
+        Crit_Min { "lcrit", Milli, Volt, false },
+        Average { "average", Milli, Volt, false },
+        Highest { "highest", Milli, Volt, false },
+        Lowest { "lowest", Milli, Volt, false },
+        Rated_Min { "rated_min", Milli, Volt, false },
+        Rated_Max { "rated_max", Milli, Volt, false },
+        Reset_History { "reset_history", Unity, Dimensionless, false },
+        Enable { "enable", Unity, Dimensionless, false },
         // Alarms
-        Alarm { "alarm", Unity, true },
-        Max_Alarm { "max_alarm", Unity, true },
-        Min_Alarm { "min_alarm", Unity, true },
-        Crit_Max_Alarm { "crit_alarm", Unity, true },
-        Crit_Min_Alarm { "lcrit_alarm", Unity, true },
-        Beep { "beep", Unity, false },
+        Alarm { "alarm", Unity, Dimensionless, true },
+        Max_Alarm { "max_alarm", Unity, Dimensionless, true },
+        Min_Alarm { "min_alarm", Unity, Dimensionless, true },
+        Crit_Max_Alarm { "crit_alarm", Unity, Dimensionless, true },
+        Crit_Min_Alarm { "lcrit_alarm", Unity, Dimensionless, true },
+        Beep { "beep", Unity, Dimensionless, false },
+        Max_Beep { "max_beep", Unity, Dimensionless, false },
+        Min_Beep { "min_beep", Unity, Dimensionless, false },
+        Crit_Max_Beep { "crit_beep", Unity, Dimensionless, false },
+        Crit_Min_Beep { "lcrit_beep", Unity, Dimensionless, false },
     ]
 }
 
@@ -140,22 +177,29 @@ make_subfeatures! {
     feature: Current,
     map: CURRENT_MAP,
     variants: [
-        Input { "input", Milli, false },
-        Max { "max", Milli, false },
-        Min { "min", Milli, false },
-        Crit_Max { "crit", Milli, false },
-        Crit_Min { "lcrit", Milli, false },
-        Average { "average", Milli, false },
-        Highest { "highest", Milli, false },
-        Lowest { "lowest", Milli, false },
-        Enable { "enable", Unity, false },
+        Input { "input", Milli, Ampere, false },
+        Max { "max", Milli, Ampere, false },
+        Min { "min", Milli, Ampere, false },
+        Crit_Max { "crit", Milli, Ampere, false },
+        Crit_Min { "lcrit", Milli, Ampere, false },
+        Average { "average", Milli, Ampere, false },
+        Highest { "highest", Milli, Ampere, false },
+        Lowest { "lowest", Milli, Ampere, false },
+        Rated_Min { "rated_min", Milli, Ampere, false },
+        Rated_Max { "rated_max", Milli, Ampere, false },
+        Reset_History { "reset_history", Unity, Dimensionless, false },
+        Enable { "enable", Unity, Dimensionless, false },
         // Alarms
-        Alarm { "alarm", Unity, true },
-        Max_Alarm { "max_alarm", Unity, true },
-        Min_Alarm { "min_alarm", Unity, true },
-        Crit_Max_Alarm { "crit_alarm", Unity, true },
-        Crit_Min_Alarm { "lcrit_alarm", Unity, true },
-        Beep { "beep", Unity, false },
+        Alarm { "alarm", Unity, Dimensionless, true },
+        Max_Alarm { "max_alarm", Unity, Dimensionless, true },
+        Min_Alarm { "min_alarm", Unity, Dimensionless, true },
+        Crit_Max_Alarm { "crit_alarm", Unity, Dimensionless, true },
+        Crit_Min_Alarm { "lcrit_alarm", Unity, Dimensionless, true },
+        Beep { "beep", Unity, Dimensionless, false },
+        Max_Beep { "max_beep", Unity, Dimensionless, false },
+        Min_Beep { "min_beep", Unity, Dimensionless, false },
+        Crit_Max_Beep { "crit_beep", Unity, Dimensionless, false },
+        Crit_Min_Beep { "lcrit_beep", Unity, Dimensionless, false },
     ]
 }
 
@@ -163,32 +207,35 @@ make_subfeatures! {
     feature: Power,
     map: POWER_MAP,
     variants: [
-        Average { "average", Micro, false },
-        Average_Highest { "average_highest", Micro, false },
-        Average_Lowest { "average_lowest", Micro, false },
-        Input { "input", Micro, false },
-        Input_Highest { "input_highest", Micro, false },
-        Input_Lowest { "input_lowest", Micro, false },
-        Cap { "cap", Micro, false },
-        Cap_Max { "cap_max", Micro, false },
-        Cap_Min { "cap_min", Micro, false },
-        Cap_Hyst { "cap_hyst", Micro, false },
-        Max { "max", Micro, false },
-        Min { "min", Micro, false },
-        Crit_Max { "crit", Micro, false },
-        Crit_Min { "lcrit", Micro, false },
-        Average_Interval { "average_interval", Milli, false },
-        Average_Interval_Max { "average_interval_max", Milli, false },
-        Average_Interval_Min { "average_interval_min", Milli, false },
-        Accuracy { "accuracy", Unity, false },
-        Enable { "enable", Unity, false },
+        Average { "average", Micro, Watt, false },
+        Average_Highest { "average_highest", Micro, Watt, false },
+        Average_Lowest { "average_lowest", Micro, Watt, false },
+        Input { "input", Micro, Watt, false },
+        Input_Highest { "input_highest", Micro, Watt, false },
+        Input_Lowest { "input_lowest", Micro, Watt, false },
+        Cap { "cap", Micro, Watt, false },
+        Cap_Max { "cap_max", Micro, Watt, false },
+        Cap_Min { "cap_min", Micro, Watt, false },
+        Cap_Hyst { "cap_hyst", Micro, Watt, false },
+        Max { "max", Micro, Watt, false },
+        Min { "min", Micro, Watt, false },
+        Crit_Max { "crit", Micro, Watt, false },
+        Crit_Min { "lcrit", Micro, Watt, false },
+        Rated_Min { "rated_min", Micro, Watt, false },
+        Rated_Max { "rated_max", Micro, Watt, false },
+        Average_Interval { "average_interval", Milli, Second, false },
+        Average_Interval_Max { "average_interval_max", Milli, Second, false },
+        Average_Interval_Min { "average_interval_min", Milli, Second, false },
+        Accuracy { "accuracy", Unity, Percent, false },
+        Reset_History { "reset_history", Unity, Dimensionless, false },
+        Enable { "enable", Unity, Dimensionless, false },
         // Alarms
-        Alarm { "alarm", Unity, true },
-        Cap_Alarm { "cap_alarm", Unity, true },
-        Max_Alarm { "max_alarm", Unity, true },
-        Min_Alarm { "min_alarm", Unity, true },
-        Crit_Max_Alarm { "crit_alarm", Unity, true },
-        Crit_Min_Alarm { "lcrit_alarm", Unity, true },
+        Alarm { "alarm", Unity, Dimensionless, true },
+        Cap_Alarm { "cap_alarm", Unity, Dimensionless, true },
+        Max_Alarm { "max_alarm", Unity, Dimensionless, true },
+        Min_Alarm { "min_alarm", Unity, Dimensionless, true },
+        Crit_Max_Alarm { "crit_alarm", Unity, Dimensionless, true },
+        Crit_Min_Alarm { "lcrit_alarm", Unity, Dimensionless, true },
     ]
 }
 
@@ -196,8 +243,12 @@ make_subfeatures! {
     feature: Energy,
     map: ENERGY_MAP,
     variants: [
-        Input { "input", Micro, false },
-        Enable { "enable", Unity, false },
+        /// Energy counter in microjoules.
+        ///
+        /// The counter can exceed 2^53, where `f64` loses integer precision.
+        /// Use [`Subfeature::read_raw_i64`] for exact arithmetic.
+        Input { "input", Micro, Joule, false },
+        Enable { "enable", Unity, Dimensionless, false },
     ]
 }
 
@@ -205,8 +256,14 @@ make_subfeatures! {
     feature: Humidity,
     map: HUMIDITY_MAP,
     variants: [
-        Input { "input", Milli, false },
-        Enable { "enable", Unity, false },
+        Input { "input", Milli, Percent, false },
+        Min { "min", Milli, Percent, false },
+        Max { "max", Milli, Percent, false },
+        Enable { "enable", Unity, Dimensionless, false },
+        // Alarms
+        Min_Alarm { "min_alarm", Unity, Dimensionless, true },
+        Max_Alarm { "max_alarm", Unity, Dimensionless, true },
+        Fault { "fault", Unity, Dimensionless, false },
     ]
 }
 
@@ -214,8 +271,8 @@ make_subfeatures! {
     feature: Intrusion,
     map: INTRUSION_MAP,
     variants: [
-        Alarm { "alarm", Micro, false },
-        Beep { "beep", Micro, false },
+        Alarm { "alarm", Unity, Dimensionless, true },
+        Beep { "beep", Unity, Dimensionless, false },
     ]
 }
 
@@ -235,15 +292,39 @@ pub enum SubfeatureType {
 }
 
 impl SubfeatureType {
-    fn to_native(self, value: f64) -> i64 {
-        (value * *self.ratio().denom() as f64 / *self.ratio().numer() as f64).round() as i64
+    /// Convert a value to the sysfs integer representation.
+    ///
+    /// Halfway values are rounded away from zero, the same way for negative and
+    /// positive values: `-0.5` becomes `-1` like `0.5` becomes `1`.
+    ///
+    /// Return `None` if the scaled value is not finite or does not fit in an `i64`.
+    pub(crate) fn to_native(self, value: f64) -> Option<i64> {
+        let ratio = self.scale_ratio();
+        let native = (value * *ratio.denom() as f64 / *ratio.numer() as f64).round();
+        // i64::MIN is exactly -2^63 as f64, and i64::MAX rounds up to 2^63.
+        if native.is_finite() && native >= i64::MIN as f64 && native < i64::MAX as f64 {
+            Some(native as i64)
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn to_unity(self, value: f64) -> f64 {
+        let ratio = self.scale_ratio();
+        value * *ratio.numer() as f64 / *ratio.denom() as f64
     }
 
-    fn to_unity(self, value: f64) -> f64 {
-        value * *self.ratio().numer() as f64 / *self.ratio().denom() as f64
+    /// Return the scale of the sysfs value, as the (numerator, denominator) ratio
+    /// giving the value in the subfeature unit.
+    ///
+    /// For instance temperatures are in millidegree Celsius, `(1, 1000)`. It allows
+    /// exact rational conversions, without going through `f64`.
+    pub fn ratio(self) -> (u64, u64) {
+        let ratio = self.scale_ratio();
+        (*ratio.numer(), *ratio.denom())
     }
 
-    fn ratio(self) -> &'static Ratio<u64> {
+    fn scale_ratio(self) -> &'static Ratio<u64> {
         match self {
             SubfeatureType::Fan(sft) => sft.ratio(),
             SubfeatureType::Pwm(sft) => sft.ratio(),
@@ -259,6 +340,28 @@ impl SubfeatureType {
         }
     }
 
+    /// Return the type of the feature the subfeature belongs to.
+    pub fn feature_type(self) -> FeatureType {
+        FeatureType::from(self)
+    }
+
+    /// Return the unit of the subfeature value.
+    pub fn unit(self) -> Unit {
+        match self {
+            SubfeatureType::Fan(sft) => sft.unit(),
+            SubfeatureType::Pwm(sft) => sft.unit(),
+            SubfeatureType::Temperature(sft) => sft.unit(),
+            SubfeatureType::Voltage(sft) => sft.unit(),
+            SubfeatureType::Current(sft) => sft.unit(),
+            SubfeatureType::Power(sft) => sft.unit(),
+            SubfeatureType::Energy(sft) => sft.unit(),
+            SubfeatureType::Humidity(sft) => sft.unit(),
+            SubfeatureType::Intrusion(sft) => sft.unit(),
+            SubfeatureType::Cpu => Unit::Volt,
+            SubfeatureType::BeepEnable => Unit::Dimensionless,
+        }
+    }
+
     /// Return `true` if the subfeature variant is an alarm.
     pub fn is_alarm(self) -> bool {
         match self {
@@ -275,6 +378,181 @@ impl SubfeatureType {
             SubfeatureType::BeepEnable => false,
         }
     }
+
+    /// Return `true` if the subfeature type is the measured value of its feature, the
+    /// one read by `Feature::input_value`.
+    ///
+    /// With `is_limit` and `is_alarm`, it sorts the subfeatures the way they are
+    /// displayed: the input, its limits, then the raised alarms.
+    pub fn is_input(self) -> bool {
+        matches!(
+            self,
+            SubfeatureType::Fan(Fan::Input)
+                | SubfeatureType::Pwm(Pwm::Pwm)
+                | SubfeatureType::Temperature(Temperature::Input)
+                | SubfeatureType::Voltage(Voltage::Input)
+                | SubfeatureType::Current(Current::Input)
+                | SubfeatureType::Power(Power::Input)
+                | SubfeatureType::Power(Power::Average)
+                | SubfeatureType::Energy(Energy::Input)
+                | SubfeatureType::Humidity(Humidity::Input)
+                | SubfeatureType::Cpu
+        )
+    }
+
+    /// Return `true` if the subfeature type is a threshold of the input, like a
+    /// `min`, `max`, `crit` or `cap` limit, or the hysteresis of one.
+    pub fn is_limit(self) -> bool {
+        match self {
+            SubfeatureType::Fan(sft) => matches!(sft, Fan::Min | Fan::Max),
+            SubfeatureType::Temperature(sft) => matches!(
+                sft,
+                Temperature::Max
+                    | Temperature::Max_Hyst
+                    | Temperature::Min
+                    | Temperature::Min_Hyst
+                    | Temperature::Crit_Max
+                    | Temperature::Crit_Max_Hyst
+                    | Temperature::Crit_Min
+                    | Temperature::Crit_Min_Hyst
+                    | Temperature::Emergency
+                    | Temperature::Emergency_Hyst
+            ),
+            SubfeatureType::Voltage(sft) => matches!(
+                sft,
+                Voltage::Max
+                    | Voltage::Min
+                    | Voltage::Crit_Max
+                    | Voltage::Crit_Min
+                    | Voltage::Rated_Min
+                    | Voltage::Rated_Max
+            ),
+            SubfeatureType::Current(sft) => matches!(
+                sft,
+                Current::Max
+                    | Current::Min
+                    | Current::Crit_Max
+                    | Current::Crit_Min
+                    | Current::Rated_Min
+                    | Current::Rated_Max
+            ),
+            SubfeatureType::Power(sft) => matches!(
+                sft,
+                Power::Cap
+                    | Power::Cap_Max
+                    | Power::Cap_Min
+                    | Power::Cap_Hyst
+                    | Power::Max
+                    | Power::Min
+                    | Power::Crit_Max
+                    | Power::Crit_Min
+                    | Power::Rated_Min
+                    | Power::Rated_Max
+            ),
+            SubfeatureType::Humidity(sft) => matches!(sft, Humidity::Min | Humidity::Max),
+            SubfeatureType::Pwm(_)
+            | SubfeatureType::Energy(_)
+            | SubfeatureType::Cpu
+            | SubfeatureType::Intrusion(_)
+            | SubfeatureType::BeepEnable => false,
+        }
+    }
+
+    /// Return a stable name of the subfeature type, made of the sysfs prefix of its
+    /// feature type and the suffix of its sysfs attribute name, like `"temp_crit_alarm"`
+    /// for `temp1_crit_alarm`. Unlike the attribute names there is no feature number.
+    pub fn canonical_name(self) -> String {
+        let suffix = match self {
+            SubfeatureType::Fan(sft) => sft.suffix(),
+            SubfeatureType::Pwm(sft) => sft.suffix(),
+            SubfeatureType::Temperature(sft) => sft.suffix(),
+            SubfeatureType::Voltage(sft) => sft.suffix(),
+            SubfeatureType::Current(sft) => sft.suffix(),
+            SubfeatureType::Power(sft) => sft.suffix(),
+            SubfeatureType::Energy(sft) => sft.suffix(),
+            SubfeatureType::Humidity(sft) => sft.suffix(),
+            SubfeatureType::Intrusion(sft) => sft.suffix(),
+            SubfeatureType::Cpu => "vid",
+            SubfeatureType::BeepEnable => "",
+        };
+        let prefix = self.feature_type().sysfs_prefix();
+        if suffix.is_empty() {
+            prefix.to_owned()
+        } else {
+            format!("{}_{}", prefix, suffix)
+        }
+    }
+
+    /// Parse a name returned by `canonical_name`, or return `None` if the name is not
+    /// the one of a subfeature type.
+    pub fn from_canonical_name(name: &str) -> Option<SubfeatureType> {
+        if name == "beep_enable" {
+            return Some(SubfeatureType::BeepEnable);
+        }
+        let (prefix, suffix) = name.split_once('_').unwrap_or((name, ""));
+        FEATURE_TYPE_MAP
+            .get(prefix)
+            .and_then(|(_, sf_map)| sf_map.get(suffix))
+            .copied()
+    }
+
+    /// Return the subfeature type of a sysfs attribute name, like `in0_max`, or
+    /// `None` if the name is not a known subfeature.
+    pub fn from_sysfs_name(name: &str) -> Option<SubfeatureType> {
+        Subfeature::get_properties_from_name(name)
+            .ok()
+            .map(|(_, sf_type)| sf_type)
+    }
+
+    /// Return the beep subfeature specific to a limit, like `temp1_crit_beep` for
+    /// `temp1_crit`, if any.
+    pub fn beep(self) -> Option<SubfeatureType> {
+        let beep = match self {
+            SubfeatureType::Temperature(Temperature::Max) => {
+                SubfeatureType::Temperature(Temperature::Max_Beep)
+            }
+            SubfeatureType::Temperature(Temperature::Min) => {
+                SubfeatureType::Temperature(Temperature::Min_Beep)
+            }
+            SubfeatureType::Temperature(Temperature::Crit_Max) => {
+                SubfeatureType::Temperature(Temperature::Crit_Max_Beep)
+            }
+            SubfeatureType::Temperature(Temperature::Crit_Min) => {
+                SubfeatureType::Temperature(Temperature::Crit_Min_Beep)
+            }
+            SubfeatureType::Voltage(Voltage::Max) => SubfeatureType::Voltage(Voltage::Max_Beep),
+            SubfeatureType::Voltage(Voltage::Min) => SubfeatureType::Voltage(Voltage::Min_Beep),
+            SubfeatureType::Voltage(Voltage::Crit_Max) => {
+                SubfeatureType::Voltage(Voltage::Crit_Max_Beep)
+            }
+            SubfeatureType::Voltage(Voltage::Crit_Min) => {
+                SubfeatureType::Voltage(Voltage::Crit_Min_Beep)
+            }
+            SubfeatureType::Current(Current::Max) => SubfeatureType::Current(Current::Max_Beep),
+            SubfeatureType::Current(Current::Min) => SubfeatureType::Current(Current::Min_Beep),
+            SubfeatureType::Current(Current::Crit_Max) => {
+                SubfeatureType::Current(Current::Crit_Max_Beep)
+            }
+            SubfeatureType::Current(Current::Crit_Min) => {
+                SubfeatureType::Current(Current::Crit_Min_Beep)
+            }
+            _ => return None,
+        };
+        Some(beep)
+    }
+
+    /// Return the hysteresis subfeature associated with a limit, if any.
+    pub fn hysteresis(self) -> Option<SubfeatureType> {
+        let hyst = match self {
+            SubfeatureType::Temperature(Temperature::Max) => Temperature::Max_Hyst,
+            SubfeatureType::Temperature(Temperature::Min) => Temperature::Min_Hyst,
+            SubfeatureType::Temperature(Temperature::Crit_Max) => Temperature::Crit_Max_Hyst,
+            SubfeatureType::Temperature(Temperature::Crit_Min) => Temperature::Crit_Min_Hyst,
+            SubfeatureType::Temperature(Temperature::Emergency) => Temperature::Emergency_Hyst,
+            _ => return None,
+        };
+        Some(SubfeatureType::Temperature(hyst))
+    }
 }
 
 static CPU_MAP: LazyLock<SubfeatureTypeMap> = LazyLock::new(|| {
@@ -304,14 +582,107 @@ static FEATURE_TYPE_MAP: LazyLock<
     m
 });
 
+/// Return the feature type of a sysfs attribute prefix, like `temp` or `in`.
+pub(crate) fn feature_type_from_prefix(prefix: &str) -> Option<FeatureType> {
+    if prefix == "beep_enable" {
+        return Some(FeatureType::BeepEnable);
+    }
+    FEATURE_TYPE_MAP
+        .get(prefix)
+        .map(|(feature_type, _)| *feature_type)
+}
+
+/// Control mode of a PWM output, as found in `pwmN_enable`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PwmEnable {
+    /// The fan runs at full speed, without any control.
+    NoControl,
+    /// The duty cycle is set manually through `pwmN`.
+    Manual,
+    /// The chip controls the duty cycle with the given driver-specific
+    /// automatic mode (2 or above).
+    Automatic(u8),
+}
+
+impl PwmEnable {
+    fn from_raw(raw: i64) -> Option<PwmEnable> {
+        match raw {
+            0 => Some(PwmEnable::NoControl),
+            1 => Some(PwmEnable::Manual),
+            _ => u8::try_from(raw).ok().map(PwmEnable::Automatic),
+        }
+    }
+
+    fn to_raw(self) -> Option<i64> {
+        match self {
+            PwmEnable::NoControl => Some(0),
+            PwmEnable::Manual => Some(1),
+            PwmEnable::Automatic(mode) if mode >= 2 => Some(mode.into()),
+            PwmEnable::Automatic(_) => None,
+        }
+    }
+}
+
+/// Kind of thermal sensor of a temperature feature, as found in `tempN_type`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TempSensorKind {
+    Disabled,
+    CpuDiode,
+    Transistor,
+    ThermalDiode,
+    Thermistor,
+    AmdAmdsi,
+    IntelPeci,
+    /// A value not defined by the hwmon sysfs interface.
+    Unknown,
+}
+
+impl TempSensorKind {
+    fn from_raw(raw: i64) -> TempSensorKind {
+        match raw {
+            0 => TempSensorKind::Disabled,
+            1 => TempSensorKind::CpuDiode,
+            2 => TempSensorKind::Transistor,
+            3 => TempSensorKind::ThermalDiode,
+            4 => TempSensorKind::Thermistor,
+            5 => TempSensorKind::AmdAmdsi,
+            6 => TempSensorKind::IntelPeci,
+            // older kernels / drivers sometimes report a beta value for thermistors
+            raw if raw > 1000 => TempSensorKind::Thermistor,
+            _ => TempSensorKind::Unknown,
+        }
+    }
+
+    fn to_raw(self) -> Option<i64> {
+        match self {
+            TempSensorKind::Disabled => Some(0),
+            TempSensorKind::CpuDiode => Some(1),
+            TempSensorKind::Transistor => Some(2),
+            TempSensorKind::ThermalDiode => Some(3),
+            TempSensorKind::Thermistor => Some(4),
+            TempSensorKind::AmdAmdsi => Some(5),
+            TempSensorKind::IntelPeci => Some(6),
+            TempSensorKind::Unknown => None,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Subfeature {
     name: String,
     path: PathBuf,
     subfeature_type: SubfeatureType,
+    feature_number: u32,
     compute_statement: Option<String>,
     is_readable: bool,
     is_writable: bool,
+    fs: Fs,
+    #[cfg(feature = "sensorsconf")]
+    config: Option<FeatureConfig>,
+    /// Compute statement set with `Feature::set_compute_statement`, it takes
+    /// precedence over the one of the configuration.
+    #[cfg(feature = "sensorsconf")]
+    compute: Option<Rc<StmtCompute>>,
 }
 
 impl Subfeature {
@@ -330,9 +701,20 @@ impl Subfeature {
         self.subfeature_type
     }
 
-    /// Return the compute statement string if specified in the configuration file.
-    /// Otherwise it return None.
+    /// Return the number of the feature the subfeature belongs to, like `3` for
+    /// `temp3_max`.
+    pub fn feature_number(&self) -> u32 {
+        self.feature_number
+    }
+
+    /// Return the compute statement of the subfeature: the one set with
+    /// `Feature::set_compute_statement` as given, or else the one of the configuration
+    /// file rendered from its parsed expressions. Return `None` if there is none.
     pub fn compute_statement(&self) -> Option<String> {
+        #[cfg(feature = "sensorsconf")]
+        if self.compute_statement.is_none() {
+            return self.computed_config()?.compute_statement();
+        }
         self.compute_statement.clone()
     }
 
@@ -347,55 +729,318 @@ impl Subfeature {
     }
 
     /// Read the value of the subfeature.
+    ///
+    /// The value is scaled to the unit of the subfeature, then the compute statement
+    /// of the configuration file, if any, is applied. See `read_value_raw_scaled` for
+    /// the value before the compute statement.
     pub fn read_value(&self) -> Result<f64, Error> {
         if self.is_readable() {
-            // TODO compute statement
-            self.read_sysfs_value()
+            self.convert_sysfs_value(&self.read_sysfs_file()?)
         } else {
             Err(Error::Access("Subfeature not readable"))
         }
     }
 
+    /// Return a reader of the subfeature value which reads the sysfs file again only
+    /// once `ttl` has elapsed since the last read, see [`CachedReader`].
+    pub fn cached_reader(&self, ttl: Duration) -> CachedReader<'_> {
+        CachedReader {
+            subfeature: self,
+            ttl,
+            last: Cell::new(None),
+        }
+    }
+
+    /// Read the value of the subfeature scaled to its unit, ignoring the compute
+    /// statement of the configuration file.
+    ///
+    /// This is the value reported by the driver, useful to calibrate or debug a
+    /// compute statement. Without configuration, it is the same as `read_value`.
+    pub fn read_value_raw_scaled(&self) -> Result<f64, Error> {
+        if self.is_readable() {
+            let raw = self.read_sysfs_file()?.parse::<f64>()?;
+            Ok(self.subfeature_type.to_unity(raw))
+        } else {
+            Err(Error::Access("Subfeature not readable"))
+        }
+    }
+
+    /// Read the value of the subfeature without blocking the async runtime.
+    ///
+    /// The scaling and the compute statement are the same as `read_value`.
+    #[cfg(feature = "async")]
+    pub async fn read_value_async(&self) -> Result<f64, Error> {
+        if self.is_readable() {
+            let contents = tokio::fs::read_to_string(&self.path)
+                .await
+                .map_err(|err| self.io_error(err))?;
+            self.convert_sysfs_value(contents.trim_end())
+        } else {
+            Err(Error::Access("Subfeature not readable"))
+        }
+    }
+
+    /// Return `true` if the subfeature is an alarm and it is currently raised.
+    ///
+    /// Return `Ok(false)` for subfeatures which are not alarms, without reading them.
+    pub fn is_alarm_active(&self) -> Result<bool, Error> {
+        if self.subfeature_type.is_alarm() {
+            Ok(self.read_value()? != 0.0)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Read the control mode of a `pwmN_enable` subfeature.
+    pub fn read_pwm_enable(&self) -> Result<PwmEnable, Error> {
+        if self.subfeature_type != SubfeatureType::Pwm(Pwm::Enable) {
+            return Err(Error::SubfeatureType);
+        }
+        PwmEnable::from_raw(self.read_raw_i64()?).ok_or(Error::InvalidValue)
+    }
+
+    /// Write the control mode of a `pwmN_enable` subfeature.
+    ///
+    /// `PwmEnable::Automatic` modes below 2 are rejected with `Error::InvalidValue`.
+    pub fn write_pwm_enable(&self, mode: PwmEnable) -> Result<(), Error> {
+        if self.subfeature_type != SubfeatureType::Pwm(Pwm::Enable) {
+            return Err(Error::SubfeatureType);
+        }
+        let raw = mode.to_raw().ok_or(Error::InvalidValue)?;
+        self.write_value(raw as f64)
+    }
+
+    /// Read the sensor kind of a `tempN_type` subfeature.
+    pub fn read_temp_type(&self) -> Result<TempSensorKind, Error> {
+        if self.subfeature_type != SubfeatureType::Temperature(Temperature::Type) {
+            return Err(Error::SubfeatureType);
+        }
+        Ok(TempSensorKind::from_raw(self.read_raw_i64()?))
+    }
+
+    /// Write the sensor kind of a `tempN_type` subfeature.
+    ///
+    /// `TempSensorKind::Unknown` has no sysfs value and is rejected with
+    /// `Error::InvalidValue`. `Error::OutOfRange` is not used since there is no numeric
+    /// value to report against the bounds.
+    pub fn write_temp_type(&self, kind: TempSensorKind) -> Result<(), Error> {
+        if self.subfeature_type != SubfeatureType::Temperature(Temperature::Type) {
+            return Err(Error::SubfeatureType);
+        }
+        let raw = kind.to_raw().ok_or(Error::InvalidValue)?;
+        self.write_value(raw as f64)
+    }
+
+    /// Read the integer value of the sysfs file, without any scaling.
+    ///
+    /// Unlike [`read_value`](Self::read_value), there is no precision loss for values
+    /// above 2^53, like accumulating energy counters.
+    pub fn read_raw_i64(&self) -> Result<i64, Error> {
+        if self.is_readable() {
+            Ok(self.read_sysfs_file()?.parse::<i64>()?)
+        } else {
+            Err(Error::Access("Subfeature not readable"))
+        }
+    }
+
+    /// Read both the integer value of the sysfs file and the scaled value, from a
+    /// single read of the file.
+    ///
+    /// The scaled value is the one returned by [`read_value`](Self::read_value).
+    pub fn read_both(&self) -> Result<(i64, f64), Error> {
+        if self.is_readable() {
+            let raw = self.read_sysfs_file()?.parse::<i64>()?;
+            Ok((raw, self.scale_raw_value(raw as f64)?))
+        } else {
+            Err(Error::Access("Subfeature not readable"))
+        }
+    }
+
+    /// Return the factor the value in the subfeature unit is multiplied by to give the
+    /// sysfs value, like `1000.0` for a temperature in millidegree Celsius.
+    ///
+    /// The compute statement of the configuration file, if any, is not part of it.
+    pub fn scale_factor(&self) -> f64 {
+        let (numer, denom) = self.subfeature_type.ratio();
+        denom as f64 / numer as f64
+    }
+
+    /// Read the content of the sysfs file as is, without parsing nor scaling.
+    ///
+    /// Trailing whitespaces are removed. This is useful for attributes the typed
+    /// API does not model, like bitmasks or vendor strings.
+    pub fn read_raw_string(&self) -> Result<String, Error> {
+        if self.is_readable() {
+            Ok(self.read_sysfs_file()?)
+        } else {
+            Err(Error::Access("Subfeature not readable"))
+        }
+    }
+
+    /// Read the value of the subfeature along with its unit.
+    pub fn read_measurement(&self) -> Result<Measurement, Error> {
+        Ok(Measurement {
+            value: self.read_value()?,
+            unit: self.subfeature_type.unit(),
+        })
+    }
+
+    /// Return the unit symbol to display after the value of the subfeature.
+    ///
+    /// Relative humidity is shown as `%RH`, and flags, modes and other
+    /// dimensionless values have an empty symbol.
+    pub fn unit_str(&self) -> &'static str {
+        match (self.subfeature_type, self.subfeature_type.unit()) {
+            (SubfeatureType::Humidity(_), Unit::Percent) => "%RH",
+            (_, unit) => unit.symbol(),
+        }
+    }
+
+    /// Return the last modification time of the sysfs file.
+    ///
+    /// A polling loop can use it to skip reading attributes that did not change.
+    /// This is best-effort only: many drivers never update the mtime of their sysfs
+    /// attributes, even though their value changes.
+    pub fn modified_time(&self) -> std::io::Result<SystemTime> {
+        self.fs.modified(&self.path)
+    }
+
     /// Write the value of the subfeature.
     ///
+    /// The integer written is the one returned by `check_write`, and nothing is
+    /// written if it returns an error.
+    ///
     /// ## Warning:
     ///
-    /// No checks are made on the value before writing it.
+    /// No checks are made on the value beyond its conversion to the sysfs integer.
     /// Affect a new value at your own risk.
     /// See hwmon and device driver documentation for more information.
     pub fn write_value(&self, value: f64) -> Result<(), Error> {
-        if self.is_writable() {
-            // TODO compute statement
-            self.write_sysfs_value(value)?;
-            Ok(())
-        } else {
-            Err(Error::Access("Subfeature not writable"))
+        let native = self.check_write(value)?;
+        self.fs
+            .write_file(&self.path, &native.to_string())
+            .map_err(|err| self.io_error(err))
+    }
+
+    /// Return the integer `write_value` would write to the sysfs file, without writing it.
+    ///
+    /// The compute statement is applied and the value is scaled like for a write.
+    /// Return `Error::Access` if the subfeature is not writable, and
+    /// `Error::InvalidValue` if the value can not be represented in sysfs, like NaN or
+    /// a value out of the `i64` range.
+    pub fn check_write(&self, value: f64) -> Result<i64, Error> {
+        if !self.is_writable() {
+            return Err(Error::Access("Subfeature not writable"));
         }
+        let value = self.compute_to_sysfs(value)?;
+        self.subfeature_type
+            .to_native(value)
+            .ok_or(Error::InvalidValue)
+    }
+
+    /// Apply the compute statement converting a value to the sysfs one, if any.
+    fn compute_to_sysfs(&self, value: f64) -> Result<f64, Error> {
+        #[cfg(feature = "sensorsconf")]
+        let value = match (&self.compute, self.computed_config()) {
+            (Some(compute), _) => compute.eval_to_proc(value)?,
+            (None, Some(config)) => config.compute_to(value)?,
+            (None, None) => value,
+        };
+        Ok(value)
     }
 
-    /// Read the value from sysfs file and apply the proper type scaling.
+    /// Write the value of the subfeature with `write_value`, then read it back.
     ///
-    /// Note: This function does not take into account the configuration file.
-    fn read_sysfs_value(&self) -> Result<f64, Error> {
-        let value = sysfs_read_file(&self.path)?.parse::<f64>()?;
-        Ok(self.subfeature_type.to_unity(value))
+    /// Drivers may silently clamp or round the written value, the value read back is
+    /// the one actually accepted. Return `Error::Access` if the subfeature is not both
+    /// readable and writable.
+    pub fn write_and_read_back(&self, value: f64) -> Result<f64, Error> {
+        if !self.is_readable() {
+            return Err(Error::Access("Subfeature not readable"));
+        }
+        self.write_value(value)?;
+        self.read_value()
+    }
+
+    #[cfg(feature = "sensorsconf")]
+    pub(crate) fn set_config(&mut self, config: Option<FeatureConfig>) {
+        self.config = config;
+    }
+
+    /// Replace the compute statement applied to the values of the subfeature.
+    #[cfg(feature = "sensorsconf")]
+    pub(crate) fn set_compute_statement(&mut self, statement: &str) -> Result<(), Error> {
+        self.compute = Some(Rc::new(parser::parse_compute_str(statement)?));
+        self.compute_statement = Some(statement.to_owned());
+        Ok(())
     }
 
-    /// Write the value to sysfs file. Before it apply the proper type scaling.
+    /// Return the parsed expression converting the values read from sysfs: the one
+    /// set with `Feature::set_compute_statement`, or else the one of the configuration
+    /// file, if any.
     ///
-    /// Note: This function does not take into account the configuration file.
-    fn write_sysfs_value(&self, value: f64) -> std::io::Result<()> {
-        let mut file = OpenOptions::new()
-            .read(false)
-            .write(true)
-            .create(false)
-            .open(&self.path)?;
-        write!(file, "{}", self.subfeature_type.to_native(value))
+    /// The expression is returned owned, since the configuration can be reloaded while
+    /// the subfeature is borrowed.
+    #[cfg(feature = "sensorsconf")]
+    pub fn compute_expr(&self) -> Option<Expr> {
+        match &self.compute {
+            Some(compute) => Some(compute.expr_from_proc().clone()),
+            None => self.computed_config()?.compute_expr(),
+        }
+    }
+
+    /// Return the configuration of the feature if the compute statement applies to
+    /// this subfeature. Alarms and other dimensionless subfeatures are never computed.
+    #[cfg(feature = "sensorsconf")]
+    fn computed_config(&self) -> Option<&FeatureConfig> {
+        if self.subfeature_type.is_alarm() || self.subfeature_type.unit() == Unit::Dimensionless {
+            None
+        } else {
+            self.config.as_ref()
+        }
+    }
+
+    /// Parse the content of the sysfs file, then apply the proper type scaling and
+    /// the compute statement of the configuration file, if any.
+    fn convert_sysfs_value(&self, contents: &str) -> Result<f64, Error> {
+        self.scale_raw_value(contents.parse::<f64>()?)
+    }
+
+    /// Apply the proper type scaling and the compute statement of the configuration
+    /// file, if any, to a raw sysfs value.
+    fn scale_raw_value(&self, raw: f64) -> Result<f64, Error> {
+        let value = self.subfeature_type.to_unity(raw);
+        #[cfg(feature = "sensorsconf")]
+        let value = match (&self.compute, self.computed_config()) {
+            (Some(compute), _) => compute.eval_from_proc(value)?,
+            (None, Some(config)) => config.compute_from(value)?,
+            (None, None) => value,
+        };
+        Ok(value)
     }
 
+    /// Read the sysfs file, tagging an IO error with its path.
+    fn read_sysfs_file(&self) -> Result<String, Error> {
+        self.fs
+            .read_file(&self.path)
+            .map_err(|err| self.io_error(err))
+    }
+
+    fn io_error(&self, source: std::io::Error) -> Error {
+        Error::IoAt {
+            path: self.path.clone(),
+            source,
+        }
+    }
+
+    #[cfg(test)]
     pub(crate) fn from_path<P: AsRef<Path>>(path: P) -> Result<(u32, Subfeature), SubfeatureError> {
-        let path = path.as_ref();
-        if !path.exists() {
+        Subfeature::from_path_in(&StdFs::shared(), path.as_ref())
+    }
+
+    /// Same as `from_path`, reading the file from the given file system.
+    pub(crate) fn from_path_in(fs: &Fs, path: &Path) -> Result<(u32, Subfeature), SubfeatureError> {
+        if !fs.is_file(path) {
             return Err(SubfeatureError::Invalid);
         }
 
@@ -403,9 +1048,7 @@ impl Subfeature {
 
         let (feature_number, subfeature_type) = Subfeature::get_properties_from_name(name)?;
 
-        let st_mode = path.metadata().map(|m| m.st_mode())?;
-        let is_readable = (st_mode & libc::S_IRUSR) == libc::S_IRUSR;
-        let is_writable = (st_mode & libc::S_IWUSR) == libc::S_IWUSR;
+        let (is_readable, is_writable) = read_permissions(fs.as_ref(), path)?;
 
         Ok((
             feature_number,
@@ -413,13 +1056,27 @@ impl Subfeature {
                 name: name.to_string(),
                 path: path.to_path_buf(),
                 subfeature_type,
-                compute_statement: None, // TODO compute statement
+                feature_number,
+                compute_statement: None,
                 is_readable,
                 is_writable,
+                fs: fs.clone(),
+                #[cfg(feature = "sensorsconf")]
+                config: None,
+                #[cfg(feature = "sensorsconf")]
+                compute: None,
             },
         ))
     }
 
+    /// Read the permissions of the sysfs file again and update `is_readable` and
+    /// `is_writable`, to pick up a change made after the chip was read, like by a
+    /// udev rule.
+    pub fn refresh_permissions(&mut self) -> std::io::Result<()> {
+        (self.is_readable, self.is_writable) = read_permissions(self.fs.as_ref(), &self.path)?;
+        Ok(())
+    }
+
     fn get_properties_from_name(name: &str) -> Result<(u32, SubfeatureType), SubfeatureError> {
         if name == "beep_enable" {
             return Ok((0, SubfeatureType::BeepEnable));
@@ -444,3 +1101,710 @@ impl Subfeature {
         }
     }
 }
+
+/// Reader of a subfeature value keeping the last value read for a while, built with
+/// `Subfeature::cached_reader`.
+///
+/// This suits user interfaces querying the same subfeature several times for each
+/// refresh. The errors are not kept, the next read tries again.
+#[derive(Debug)]
+pub struct CachedReader<'a> {
+    subfeature: &'a Subfeature,
+    ttl: Duration,
+    last: Cell<Option<(Instant, f64)>>,
+}
+
+impl CachedReader<'_> {
+    /// Return the last value read if it is more recent than the time to live, or
+    /// read it again like `Subfeature::read_value`.
+    pub fn read_value(&self) -> Result<f64, Error> {
+        if let Some((instant, value)) = self.last.get() {
+            if instant.elapsed() < self.ttl {
+                return Ok(value);
+            }
+        }
+        let value = self.subfeature.read_value()?;
+        self.last.set(Some((Instant::now(), value)));
+        Ok(value)
+    }
+
+    /// Drop the value kept, so that the next read reads the sysfs file.
+    pub fn invalidate(&self) {
+        self.last.set(None);
+    }
+}
+
+/// Reader of the energy used between two reads of an `energyN_input` counter.
+///
+/// The counter is read as an integer with `Subfeature::read_raw_i64`, without the
+/// precision loss of `f64` on large values. A value lower than the previous one is
+/// taken as a wrap of the counter, at 2^32 if the previous value fits in 32 bits,
+/// else at 2^63.
+#[derive(Debug)]
+pub struct EnergyCounter<'a> {
+    subfeature: &'a Subfeature,
+    previous: Option<i64>,
+}
+
+impl<'a> EnergyCounter<'a> {
+    pub fn new(subfeature: &'a Subfeature) -> EnergyCounter<'a> {
+        EnergyCounter {
+            subfeature,
+            previous: None,
+        }
+    }
+
+    /// Read the counter and return the energy used since the previous call, in the
+    /// unit of the subfeature, joules for energy counters.
+    ///
+    /// Return `None` on the first call, and when the counter can not be read. In the
+    /// latter case the next call still returns the energy since the last read.
+    pub fn delta(&mut self) -> Option<f64> {
+        let current = self.subfeature.read_raw_i64().ok()?;
+        let previous = self.previous.replace(current)?;
+
+        let delta = if current >= previous {
+            i128::from(current) - i128::from(previous)
+        } else {
+            let wrap = if previous <= i64::from(u32::MAX) {
+                1i128 << 32
+            } else {
+                1i128 << 63
+            };
+            wrap - i128::from(previous) + i128::from(current)
+        };
+        Some(self.subfeature.subfeature_type.to_unity(delta as f64))
+    }
+}
+
+/// Return whether the owner of the file can read it and write it.
+pub(crate) fn read_permissions(fs: &dyn FsProvider, path: &Path) -> std::io::Result<(bool, bool)> {
+    let st_mode = fs.mode(path)?;
+    Ok((
+        (st_mode & libc::S_IRUSR) == libc::S_IRUSR,
+        (st_mode & libc::S_IWUSR) == libc::S_IWUSR,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::TempDir;
+
+    #[test]
+    fn subfeature_type_unit() {
+        assert_eq!(
+            SubfeatureType::Temperature(Temperature::Input).unit(),
+            Unit::Celsius
+        );
+        assert_eq!(
+            SubfeatureType::Temperature(Temperature::Max_Alarm).unit(),
+            Unit::Dimensionless
+        );
+        assert_eq!(SubfeatureType::Fan(Fan::Input).unit(), Unit::Rpm);
+        assert_eq!(
+            SubfeatureType::Power(Power::Average_Interval).unit(),
+            Unit::Second
+        );
+        assert_eq!(SubfeatureType::Cpu.unit(), Unit::Volt);
+    }
+
+    #[test]
+    fn subfeature_unit_str() {
+        let dir = TempDir::new();
+        let unit_str = |name: &str| {
+            let (_, subfeature) = Subfeature::from_path(dir.file(name, "0")).unwrap();
+            subfeature.unit_str()
+        };
+
+        assert_eq!(unit_str("curr1_input"), "A");
+        assert_eq!(unit_str("power1_average_interval"), "s");
+        assert_eq!(unit_str("humidity1_input"), "%RH");
+        assert_eq!(unit_str("temp1_input"), "°C");
+        assert_eq!(unit_str("in0_alarm"), "");
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn subfeature_read_value_async() {
+        let dir = TempDir::new();
+        let path = dir.file("in0_input", "1200");
+        let (_, subfeature) = Subfeature::from_path(path).unwrap();
+
+        assert_eq!(subfeature.read_value_async().await.unwrap(), 1.2);
+        assert_eq!(
+            subfeature.read_value_async().await.unwrap(),
+            subfeature.read_value().unwrap()
+        );
+    }
+
+    #[test]
+    fn subfeature_type_canonical_name() {
+        for (sf_type, name) in [
+            (
+                SubfeatureType::Temperature(Temperature::Crit_Max_Alarm),
+                "temp_crit_alarm",
+            ),
+            (SubfeatureType::Fan(Fan::Min), "fan_min"),
+            (SubfeatureType::Voltage(Voltage::Input), "in_input"),
+            (
+                SubfeatureType::Power(Power::Average_Interval),
+                "power_average_interval",
+            ),
+            (SubfeatureType::Pwm(Pwm::Pwm), "pwm"),
+            (SubfeatureType::Pwm(Pwm::Enable), "pwm_enable"),
+            (SubfeatureType::Cpu, "cpu_vid"),
+            (SubfeatureType::BeepEnable, "beep_enable"),
+        ] {
+            assert_eq!(sf_type.canonical_name(), name);
+            assert_eq!(SubfeatureType::from_canonical_name(name), Some(sf_type));
+        }
+
+        for feature_type in [FeatureType::Temperature, FeatureType::Power] {
+            for sf_type in feature_type.subfeature_types() {
+                let name = sf_type.canonical_name();
+                assert_eq!(SubfeatureType::from_canonical_name(&name), Some(*sf_type));
+            }
+        }
+        assert_eq!(SubfeatureType::from_canonical_name("temp1_input"), None);
+        assert_eq!(SubfeatureType::from_canonical_name("temp_foo"), None);
+    }
+
+    #[test]
+    fn subfeature_type_classification() {
+        let input = SubfeatureType::Voltage(Voltage::Input);
+        assert!(input.is_input());
+        assert!(!input.is_limit());
+        assert!(!input.is_alarm());
+
+        let limit = SubfeatureType::Voltage(Voltage::Crit_Min);
+        assert!(!limit.is_input());
+        assert!(limit.is_limit());
+        assert!(!limit.is_alarm());
+
+        let alarm = SubfeatureType::Voltage(Voltage::Max_Alarm);
+        assert!(!alarm.is_input());
+        assert!(!alarm.is_limit());
+        assert!(alarm.is_alarm());
+
+        let other = SubfeatureType::Voltage(Voltage::Average);
+        assert!(!other.is_input() && !other.is_limit() && !other.is_alarm());
+    }
+
+    #[test]
+    fn subfeature_cached_reader() {
+        let dir = TempDir::new();
+        let (_, subfeature) = Subfeature::from_path(dir.file("temp1_input", "45000")).unwrap();
+
+        let reader = subfeature.cached_reader(Duration::from_secs(60));
+        assert_eq!(reader.read_value().unwrap(), 45.0);
+        dir.file("temp1_input", "50000");
+        assert_eq!(reader.read_value().unwrap(), 45.0);
+        assert_eq!(subfeature.read_value().unwrap(), 50.0);
+        reader.invalidate();
+        assert_eq!(reader.read_value().unwrap(), 50.0);
+
+        let reader = subfeature.cached_reader(Duration::from_millis(10));
+        assert_eq!(reader.read_value().unwrap(), 50.0);
+        dir.file("temp1_input", "55000");
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(reader.read_value().unwrap(), 55.0);
+    }
+
+    #[test]
+    fn energy_counter_wrap() {
+        let dir = TempDir::new();
+        let (_, subfeature) =
+            Subfeature::from_path(dir.file("energy1_input", "4294000000")).unwrap();
+
+        let mut counter = EnergyCounter::new(&subfeature);
+        assert_eq!(counter.delta(), None);
+        dir.file("energy1_input", "4294500000");
+        assert_eq!(counter.delta(), Some(0.5));
+
+        // 32-bit wrap: 467296 µJ up to 2^32, then 32704 µJ
+        dir.file("energy1_input", "32704");
+        assert_eq!(counter.delta(), Some(0.5));
+
+        std::fs::remove_file(subfeature.path()).unwrap();
+        assert_eq!(counter.delta(), None);
+        dir.file("energy1_input", "2032704");
+        assert_eq!(counter.delta(), Some(2.0));
+    }
+
+    #[test]
+    fn subfeature_scale_factor() {
+        let dir = TempDir::new();
+        let (_, subfeature) = Subfeature::from_path(dir.file("temp1_input", "45000")).unwrap();
+        assert_eq!(subfeature.scale_factor(), 1000.0);
+        let (_, subfeature) = Subfeature::from_path(dir.file("fan1_input", "1500")).unwrap();
+        assert_eq!(subfeature.scale_factor(), 1.0);
+        let (_, subfeature) = Subfeature::from_path(dir.file("power1_input", "1500")).unwrap();
+        assert_eq!(subfeature.scale_factor(), 1_000_000.0);
+    }
+
+    #[test]
+    fn subfeature_read_both() {
+        let dir = TempDir::new();
+        let (_, subfeature) = Subfeature::from_path(dir.file("temp1_input", "45000")).unwrap();
+        assert_eq!(subfeature.read_both().unwrap(), (45000, 45.0));
+
+        for raw in [-273150, 0, 1, 45123, 99999] {
+            dir.file("temp1_input", &raw.to_string());
+            let (read_raw, scaled) = subfeature.read_both().unwrap();
+            assert_eq!(read_raw, raw);
+            assert_eq!(subfeature.get_type().to_native(scaled), Some(raw));
+        }
+    }
+
+    #[test]
+    fn subfeature_type_feature_type() {
+        let cases = [
+            (SubfeatureType::Fan(Fan::Input), FeatureType::Fan),
+            (SubfeatureType::Pwm(Pwm::Enable), FeatureType::Pwm),
+            (
+                SubfeatureType::Temperature(Temperature::Max),
+                FeatureType::Temperature,
+            ),
+            (SubfeatureType::Voltage(Voltage::Min), FeatureType::Voltage),
+            (
+                SubfeatureType::Current(Current::Input),
+                FeatureType::Current,
+            ),
+            (SubfeatureType::Power(Power::Cap), FeatureType::Power),
+            (SubfeatureType::Energy(Energy::Input), FeatureType::Energy),
+            (
+                SubfeatureType::Humidity(Humidity::Input),
+                FeatureType::Humidity,
+            ),
+            (SubfeatureType::Cpu, FeatureType::Cpu),
+            (
+                SubfeatureType::Intrusion(Intrusion::Alarm),
+                FeatureType::Intrusion,
+            ),
+            (SubfeatureType::BeepEnable, FeatureType::BeepEnable),
+        ];
+        for (sf_type, feature_type) in cases {
+            assert_eq!(sf_type.feature_type(), feature_type);
+        }
+    }
+
+    #[test]
+    fn subfeature_refresh_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new();
+        let path = dir.file_with_mode("temp1_max", "80000", 0o444);
+        let (_, mut subfeature) = Subfeature::from_path(&path).unwrap();
+        assert!(!subfeature.is_writable());
+
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        assert!(!subfeature.is_writable());
+        subfeature.refresh_permissions().unwrap();
+        assert!(subfeature.is_writable());
+        assert!(subfeature.is_readable());
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(subfeature.refresh_permissions().is_err());
+    }
+
+    #[test]
+    fn subfeature_feature_number() {
+        let dir = TempDir::new();
+        let (number, subfeature) = Subfeature::from_path(dir.file("temp3_max", "80000")).unwrap();
+        assert_eq!(number, 3);
+        assert_eq!(subfeature.feature_number(), 3);
+
+        let (_, subfeature) = Subfeature::from_path(dir.file("beep_enable", "1")).unwrap();
+        assert_eq!(subfeature.feature_number(), 0);
+    }
+
+    #[test]
+    fn subfeature_properties_from_name() {
+        assert_eq!(
+            Subfeature::get_properties_from_name("temp1_crit_alarm").unwrap(),
+            (1, SubfeatureType::Temperature(Temperature::Crit_Max_Alarm))
+        );
+        assert_eq!(
+            Subfeature::get_properties_from_name("temp1_crit").unwrap(),
+            (1, SubfeatureType::Temperature(Temperature::Crit_Max))
+        );
+        assert_eq!(
+            Subfeature::get_properties_from_name("temp10_input").unwrap(),
+            (10, SubfeatureType::Temperature(Temperature::Input))
+        );
+        assert!(matches!(
+            Subfeature::get_properties_from_name("temp99999999999_input"),
+            Err(SubfeatureError::ParseInt(_))
+        ));
+        assert!(matches!(
+            Subfeature::get_properties_from_name("temp_input"),
+            Err(SubfeatureError::Invalid)
+        ));
+    }
+
+    #[test]
+    fn subfeature_type_from_sysfs_name() {
+        assert_eq!(
+            SubfeatureType::from_sysfs_name("power1_cap_alarm"),
+            Some(SubfeatureType::Power(Power::Cap_Alarm))
+        );
+        assert_eq!(
+            SubfeatureType::from_sysfs_name("in0_max"),
+            Some(SubfeatureType::Voltage(Voltage::Max))
+        );
+        assert_eq!(
+            SubfeatureType::from_sysfs_name("pwm2"),
+            Some(SubfeatureType::Pwm(Pwm::Pwm))
+        );
+        assert_eq!(
+            SubfeatureType::from_sysfs_name("beep_enable"),
+            Some(SubfeatureType::BeepEnable)
+        );
+        assert_eq!(SubfeatureType::from_sysfs_name("temp1_label"), None);
+        assert_eq!(SubfeatureType::from_sysfs_name("update_interval"), None);
+    }
+
+    #[test]
+    fn subfeature_rated() {
+        let dir = TempDir::new();
+        let (number, subfeature) =
+            Subfeature::from_path(dir.file("in0_rated_max", "3300")).unwrap();
+        assert_eq!(number, 0);
+        assert_eq!(
+            subfeature.get_type(),
+            SubfeatureType::Voltage(Voltage::Rated_Max)
+        );
+        assert!(!subfeature.get_type().is_alarm());
+        assert_eq!(subfeature.read_value().unwrap(), 3.3);
+
+        assert_eq!(
+            SubfeatureType::from_sysfs_name("curr1_rated_min"),
+            Some(SubfeatureType::Current(Current::Rated_Min))
+        );
+        assert_eq!(
+            SubfeatureType::from_sysfs_name("power1_rated_max"),
+            Some(SubfeatureType::Power(Power::Rated_Max))
+        );
+        assert!(!SubfeatureType::Power(Power::Rated_Max).is_alarm());
+    }
+
+    #[test]
+    fn subfeature_write_and_read_back() {
+        let dir = TempDir::new();
+        let (_, subfeature) = Subfeature::from_path(dir.file("temp1_max", "80000")).unwrap();
+        assert_eq!(subfeature.write_and_read_back(75.5).unwrap(), 75.5);
+        assert_eq!(sysfs_read_file(subfeature.path()).unwrap(), "75500");
+
+        let path = dir.file_with_mode("temp1_input", "45000", 0o444);
+        let (_, subfeature) = Subfeature::from_path(path).unwrap();
+        assert!(matches!(
+            subfeature.write_and_read_back(50.0),
+            Err(Error::Access(_))
+        ));
+
+        let path = dir.file_with_mode("temp1_reset_history", "", 0o200);
+        let (_, subfeature) = Subfeature::from_path(path).unwrap();
+        assert!(matches!(
+            subfeature.write_and_read_back(1.0),
+            Err(Error::Access(_))
+        ));
+    }
+
+    #[test]
+    fn subfeature_check_write() {
+        let dir = TempDir::new();
+        let (_, subfeature) = Subfeature::from_path(dir.file("temp1_max", "80000")).unwrap();
+        assert_eq!(subfeature.check_write(75.5).unwrap(), 75500);
+        assert_eq!(subfeature.check_write(-0.5).unwrap(), -500);
+        assert_eq!(sysfs_read_file(subfeature.path()).unwrap(), "80000");
+        assert!(matches!(
+            subfeature.check_write(f64::NAN),
+            Err(Error::InvalidValue)
+        ));
+        assert!(matches!(
+            subfeature.check_write(f64::INFINITY),
+            Err(Error::InvalidValue)
+        ));
+        assert!(matches!(
+            subfeature.check_write(-1e30),
+            Err(Error::InvalidValue)
+        ));
+
+        assert!(matches!(
+            subfeature.write_value(f64::NAN),
+            Err(Error::InvalidValue)
+        ));
+        assert_eq!(sysfs_read_file(subfeature.path()).unwrap(), "80000");
+
+        let path = dir.file_with_mode("temp1_input", "45000", 0o444);
+        let (_, subfeature) = Subfeature::from_path(path).unwrap();
+        assert!(matches!(
+            subfeature.check_write(50.0),
+            Err(Error::Access(_))
+        ));
+    }
+
+    #[test]
+    fn subfeature_read_value_raw_scaled() {
+        let dir = TempDir::new();
+        let (_, subfeature) = Subfeature::from_path(dir.file("in0_input", "1200")).unwrap();
+        assert_eq!(subfeature.read_value_raw_scaled().unwrap(), 1.2);
+        assert_eq!(
+            subfeature.read_value_raw_scaled().unwrap(),
+            subfeature.read_value().unwrap()
+        );
+    }
+
+    #[cfg(feature = "sensorsconf")]
+    #[test]
+    fn subfeature_read_value_raw_scaled_compute() {
+        use crate::context::Context;
+        use crate::feature::FeatureType;
+        use crate::testutil::fake_chip_with_context;
+
+        let dir = TempDir::new();
+        let conf = dir.file(
+            "sensors.conf",
+            "chip \"dummy-*\"\n    compute temp1 @*2, @/2\n",
+        );
+        let context = Context::with_config(&conf).unwrap();
+        let (_chip_dir, chip) =
+            fake_chip_with_context(&context, "dummy", &[("temp1_input", "45000")]);
+        let subfeature = chip
+            .feature(FeatureType::Temperature, 1)
+            .unwrap()
+            .subfeature(SubfeatureType::Temperature(Temperature::Input))
+            .unwrap();
+
+        assert_eq!(subfeature.read_value().unwrap(), 90.0);
+        assert_eq!(subfeature.read_value_raw_scaled().unwrap(), 45.0);
+    }
+
+    #[cfg(feature = "sensorsconf")]
+    #[test]
+    fn subfeature_compute_expr_from_config() {
+        use crate::context::Context;
+        use crate::feature::FeatureType;
+        use crate::testutil::fake_chip_with_context;
+
+        let dir = TempDir::new();
+        let conf = dir.file(
+            "sensors.conf",
+            "chip \"dummy-*\"\n    compute temp1 @*2+1, (@-1)/2\n",
+        );
+        let context = Context::with_config(&conf).unwrap();
+        let (_chip_dir, chip) = fake_chip_with_context(
+            &context,
+            "dummy",
+            &[("temp1_input", "45000"), ("temp1_max_alarm", "0")],
+        );
+        let temp = chip.feature(FeatureType::Temperature, 1).unwrap();
+
+        let input = temp
+            .subfeature(SubfeatureType::Temperature(Temperature::Input))
+            .unwrap();
+        assert_eq!(input.compute_expr().unwrap().to_string(), "@ * 2 + 1");
+        assert_eq!(
+            input.compute_statement().as_deref(),
+            Some("@ * 2 + 1, (@ - 1) / 2")
+        );
+        let alarm = temp
+            .subfeature(SubfeatureType::Temperature(Temperature::Max_Alarm))
+            .unwrap();
+        assert!(alarm.compute_expr().is_none());
+        assert!(alarm.compute_statement().is_none());
+    }
+
+    #[cfg(feature = "sensorsconf")]
+    #[test]
+    fn subfeature_compute_expr_round_trip() {
+        let dir = TempDir::new();
+        let (_, mut subfeature) = Subfeature::from_path(dir.file("temp1_input", "45000")).unwrap();
+        assert!(subfeature.compute_expr().is_none());
+
+        subfeature.set_compute_statement("@*2+1, (@-1)/2").unwrap();
+        let rendered = subfeature.compute_expr().unwrap().to_string();
+        assert_eq!(rendered, "@ * 2 + 1");
+
+        let reparsed = parser::parse_compute_str(&format!("{}, @", rendered)).unwrap();
+        assert_eq!(
+            Some(reparsed.expr_from_proc()),
+            subfeature.compute_expr().as_ref()
+        );
+        assert_eq!(subfeature.read_value().unwrap(), 91.0);
+    }
+
+    #[test]
+    fn subfeature_io_error_path() {
+        let dir = TempDir::new();
+        let path = dir.file("temp1_input", "45000");
+        let (_, subfeature) = Subfeature::from_path(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let err = subfeature.read_value().unwrap_err();
+        assert!(matches!(err, Error::IoAt { ref path, .. } if path == subfeature.path()));
+        assert!(err.to_string().contains(&path.display().to_string()));
+
+        let err = subfeature.write_value(50.0).unwrap_err();
+        assert!(err.to_string().contains(&path.display().to_string()));
+    }
+
+    #[test]
+    fn subfeature_modified_time() {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let dir = TempDir::new();
+        let path = dir.file("temp1_max", "80000");
+        let old = UNIX_EPOCH + Duration::from_secs(1_000_000);
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+
+        let (_, subfeature) = Subfeature::from_path(&path).unwrap();
+        assert_eq!(subfeature.modified_time().unwrap(), old);
+        subfeature.write_value(75.0).unwrap();
+        assert!(subfeature.modified_time().unwrap() > old);
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(subfeature.modified_time().is_err());
+    }
+
+    #[test]
+    fn subfeature_type_ratio() {
+        assert_eq!(
+            SubfeatureType::Temperature(Temperature::Input).ratio(),
+            (*Milli.numer(), *Milli.denom())
+        );
+        assert_eq!(
+            SubfeatureType::Temperature(Temperature::Input).ratio(),
+            (1, 1000)
+        );
+        assert_eq!(SubfeatureType::Power(Power::Input).ratio(), (1, 1_000_000));
+        assert_eq!(SubfeatureType::Fan(Fan::Input).ratio(), (1, 1));
+    }
+
+    #[test]
+    fn subfeature_power_average_interval_seconds() {
+        let dir = TempDir::new();
+        let (_, subfeature) =
+            Subfeature::from_path(dir.file("power1_average_interval", "1500")).unwrap();
+        assert_eq!(
+            subfeature.read_measurement().unwrap(),
+            Measurement {
+                value: 1.5,
+                unit: Unit::Second,
+            }
+        );
+        assert_eq!(subfeature.unit_str(), "s");
+    }
+
+    #[test]
+    fn subfeature_negative_voltage() {
+        let dir = TempDir::new();
+        let (_, subfeature) = Subfeature::from_path(dir.file("in5_input", "-12000")).unwrap();
+        assert_eq!(subfeature.read_value().unwrap(), -12.0);
+
+        let (_, subfeature) = Subfeature::from_path(dir.file("in5_min", "-13000")).unwrap();
+        subfeature.write_value(-12.0).unwrap();
+        assert_eq!(sysfs_read_file(subfeature.path()).unwrap(), "-12000");
+        assert_eq!(subfeature.read_value().unwrap(), -12.0);
+    }
+
+    #[test]
+    fn subfeature_type_to_native_rounding() {
+        let volt = SubfeatureType::Voltage(Voltage::Min);
+        assert_eq!(volt.to_native(-12.0), Some(-12000));
+        assert_eq!(volt.to_native(-11.9996), Some(-12000));
+
+        let pwm = SubfeatureType::Pwm(Pwm::Pwm);
+        assert_eq!(pwm.to_native(0.5), Some(1));
+        assert_eq!(pwm.to_native(-0.5), Some(-1));
+        assert_eq!(pwm.to_native(-1.5), Some(-2));
+        assert_eq!(pwm.to_native(-0.4), Some(0));
+
+        assert_eq!(pwm.to_native(f64::NAN), None);
+        assert_eq!(pwm.to_native(-1e30), None);
+        assert_eq!(pwm.to_native(1e30), None);
+    }
+
+    #[test]
+    fn subfeature_read_measurement() {
+        let dir = TempDir::new();
+        let path = dir.file("temp1_input", "45000");
+        let (_, subfeature) = Subfeature::from_path(path).unwrap();
+
+        let measurement = subfeature.read_measurement().unwrap();
+        assert_eq!(measurement.value, 45.0);
+        assert_eq!(measurement.unit, Unit::Celsius);
+    }
+
+    #[test]
+    fn subfeature_read_raw_i64_exact() {
+        let dir = TempDir::new();
+        let path = dir.file("energy1_input", "9007199254740993");
+        let (_, subfeature) = Subfeature::from_path(path).unwrap();
+
+        assert_eq!(subfeature.read_raw_i64().unwrap(), 9007199254740993);
+    }
+
+    #[test]
+    fn subfeature_read_raw_string_non_numeric() {
+        let dir = TempDir::new();
+        let path = dir.file("temp1_input", "N/A\n");
+        let (_, subfeature) = Subfeature::from_path(path).unwrap();
+
+        assert!(matches!(subfeature.read_value(), Err(Error::ParseFloat(_))));
+        assert_eq!(subfeature.read_raw_string().unwrap(), "N/A");
+    }
+
+    #[test]
+    fn subfeature_pwm_enable_round_trip() {
+        let dir = TempDir::new();
+        let path = dir.file("pwm1_enable", "2");
+        let (_, subfeature) = Subfeature::from_path(&path).unwrap();
+
+        assert_eq!(
+            subfeature.read_pwm_enable().unwrap(),
+            PwmEnable::Automatic(2)
+        );
+
+        subfeature.write_pwm_enable(PwmEnable::Manual).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "1");
+        assert_eq!(subfeature.read_pwm_enable().unwrap(), PwmEnable::Manual);
+
+        assert!(matches!(
+            subfeature.write_pwm_enable(PwmEnable::Automatic(1)),
+            Err(Error::InvalidValue)
+        ));
+    }
+
+    #[test]
+    fn subfeature_pwm_enable_wrong_type() {
+        let dir = TempDir::new();
+        let path = dir.file("pwm1", "128");
+        let (_, subfeature) = Subfeature::from_path(path).unwrap();
+
+        assert!(matches!(
+            subfeature.read_pwm_enable(),
+            Err(Error::SubfeatureType)
+        ));
+    }
+
+    #[test]
+    fn subfeature_humidity_max() {
+        let dir = TempDir::new();
+        let path = dir.file("humidity1_max", "85500");
+        let (number, subfeature) = Subfeature::from_path(path).unwrap();
+
+        assert_eq!(number, 1);
+        assert_eq!(
+            subfeature.get_type(),
+            SubfeatureType::Humidity(Humidity::Max)
+        );
+        assert_eq!(subfeature.read_value().unwrap(), 85.5);
+    }
+}
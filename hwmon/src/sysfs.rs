@@ -1,25 +1,216 @@
 // SPDX-FileCopyrightText: 2018 Camille019
 // SPDX-License-Identifier: MPL-2.0
 
-use std::fs::OpenOptions;
-use std::io::{self, Read};
-use std::path::Path;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Read, Write};
+use std::os::linux::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::SystemTime;
+
+use crate::error::Error;
 
 pub const SYSFS_MOUNT: &str = "/sys";
 
+/// The file system operations made on the sysfs tree, so that a context can be built
+/// over an in-memory tree instead of the real file system, see `MockFs`.
+pub(crate) trait FsProvider: fmt::Debug {
+    /// Read a file, with the trailing whitespaces removed.
+    fn read_file(&self, path: &Path) -> io::Result<String>;
+
+    /// Replace the content of an existing file.
+    fn write_file(&self, path: &Path, contents: &str) -> io::Result<()>;
+
+    /// Return the paths of the entries of a directory, in arbitrary order.
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>>;
+
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf>;
+
+    /// Return the absolute path of a file, with the `.` and `..` components removed and
+    /// the symbolic links resolved.
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf>;
+
+    /// Return the `st_mode` of a file.
+    fn mode(&self, path: &Path) -> io::Result<u32>;
+
+    fn modified(&self, path: &Path) -> io::Result<SystemTime>;
+
+    fn is_dir(&self, path: &Path) -> bool;
+
+    fn is_file(&self, path: &Path) -> bool;
+
+    fn read_attr(&self, path: &Path, attr: &str) -> io::Result<String> {
+        self.read_file(&path.join(attr))
+    }
+
+    fn write_attr(&self, path: &Path, attr: &str, contents: &str) -> io::Result<()> {
+        self.write_file(&path.join(attr), contents)
+    }
+}
+
+/// Shared handle on the file system of a context.
+pub(crate) type Fs = Rc<dyn FsProvider>;
+
+/// The real file system.
+#[derive(Debug)]
+pub(crate) struct StdFs;
+
+impl StdFs {
+    pub(crate) fn shared() -> Fs {
+        Rc::new(StdFs)
+    }
+}
+
+impl FsProvider for StdFs {
+    fn read_file(&self, path: &Path) -> io::Result<String> {
+        sysfs_read_file(path)
+    }
+
+    fn write_file(&self, path: &Path, contents: &str) -> io::Result<()> {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(false)
+            .truncate(true)
+            .open(path)?;
+        file.write_all(contents.as_bytes())
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect()
+    }
+
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        path.read_link()
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        path.canonicalize()
+    }
+
+    fn mode(&self, path: &Path) -> io::Result<u32> {
+        Ok(path.metadata()?.st_mode())
+    }
+
+    fn modified(&self, path: &Path) -> io::Result<SystemTime> {
+        path.metadata()?.modified()
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        path.is_file()
+    }
+}
+
+/// Number of reads of an attribute before giving up on a transient error.
+const READ_ATTEMPTS: u32 = 3;
+
 pub fn sysfs_read_file(path: &Path) -> io::Result<String> {
     let mut file = OpenOptions::new().read(true).write(false).open(path)?;
-    let mut buf: String = String::new();
-    file.read_to_string(&mut buf)?;
+    let mut buf = read_retrying(&mut file)?;
     let len = buf.trim_end().len();
     buf.truncate(len);
 
     Ok(buf)
 }
 
-pub fn sysfs_read_attr(path: &Path, attr: &str) -> io::Result<String> {
-    let mut path = path.to_owned();
-    path.push(attr);
+/// Read until end of file, retrying on `Interrupted` and `WouldBlock` errors which busy
+/// chips may return. The bytes read before an error are kept, the next read continues
+/// after them.
+fn read_retrying<R: Read>(reader: &mut R) -> io::Result<String> {
+    let mut buf = Vec::new();
+    let mut attempts = 1;
+    loop {
+        match reader.read_to_end(&mut buf) {
+            Ok(_) => break,
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::Interrupted | io::ErrorKind::WouldBlock
+                ) && attempts < READ_ATTEMPTS =>
+            {
+                attempts += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    String::from_utf8(buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Check that a sysfs file system is mounted at `root`. An unmounted sysfs mount
+/// point is usually an empty directory, so the `class` directory is looked for.
+pub(crate) fn sysfs_check_root(fs: &dyn FsProvider, root: &Path) -> Result<(), Error> {
+    if fs.is_dir(&root.join("class")) {
+        Ok(())
+    } else {
+        Err(Error::SysfsUnavailable(root.to_owned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Read};
+
+    use super::{read_retrying, READ_ATTEMPTS};
+
+    /// A reader failing with the given error a number of times before returning its data.
+    struct FlakyReader {
+        kind: io::ErrorKind,
+        failures: u32,
+        data: &'static [u8],
+    }
+
+    impl Read for FlakyReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.failures > 0 {
+                self.failures -= 1;
+                return Err(io::Error::from(self.kind));
+            }
+            let len = self.data.len().min(buf.len());
+            buf[..len].copy_from_slice(&self.data[..len]);
+            self.data = &self.data[len..];
+            Ok(len)
+        }
+    }
+
+    #[test]
+    fn read_retrying_transient_errors() {
+        for kind in [io::ErrorKind::Interrupted, io::ErrorKind::WouldBlock] {
+            let mut reader = FlakyReader {
+                kind,
+                failures: 1,
+                data: b"45000\n",
+            };
+            assert_eq!(read_retrying(&mut reader).unwrap(), "45000\n");
+        }
+    }
+
+    #[test]
+    fn read_retrying_gives_up() {
+        let mut reader = FlakyReader {
+            kind: io::ErrorKind::WouldBlock,
+            failures: READ_ATTEMPTS,
+            data: b"45000\n",
+        };
+        assert_eq!(
+            read_retrying(&mut reader).unwrap_err().kind(),
+            io::ErrorKind::WouldBlock
+        );
 
-    sysfs_read_file(path.as_ref())
+        let mut reader = FlakyReader {
+            kind: io::ErrorKind::PermissionDenied,
+            failures: 1,
+            data: b"45000\n",
+        };
+        assert_eq!(
+            read_retrying(&mut reader).unwrap_err().kind(),
+            io::ErrorKind::PermissionDenied
+        );
+    }
 }
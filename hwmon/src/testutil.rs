@@ -0,0 +1,89 @@
+// SPDX-FileCopyrightText: 2026 Camille019
+// SPDX-License-Identifier: MPL-2.0
+
+//! Helpers to fabricate sysfs trees for unit tests.
+
+#![allow(dead_code)]
+
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::chip::Chip;
+use crate::context::Context;
+
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A temporary directory removed on drop.
+pub(crate) struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    pub(crate) fn new() -> TempDir {
+        let path = std::env::temp_dir().join(format!(
+            "hwmon-test-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&path).unwrap();
+        TempDir { path }
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        self.path.as_ref()
+    }
+
+    /// Create a file with the given contents, and its parent directories.
+    pub(crate) fn file(&self, name: &str, contents: &str) -> PathBuf {
+        self.file_with_mode(name, contents, 0o644)
+    }
+
+    /// Create a file with the given contents and permission bits.
+    pub(crate) fn file_with_mode(&self, name: &str, contents: &str, mode: u32) -> PathBuf {
+        let path = self.path.join(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        fs::write(&path, contents).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+        path
+    }
+
+    /// Create a symbolic link `name` pointing to `target`, and its parent directories.
+    pub(crate) fn symlink(&self, name: &str, target: &str) -> PathBuf {
+        let path = self.path.join(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        std::os::unix::fs::symlink(target, &path).unwrap();
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+/// Build a virtual chip named `prefix` from a list of `(attribute, contents)` files.
+pub(crate) fn fake_chip(prefix: &str, files: &[(&str, &str)]) -> (TempDir, Chip) {
+    fake_chip_with_context(&Context::empty(), prefix, files)
+}
+
+/// Same as `fake_chip`, with the given context.
+pub(crate) fn fake_chip_with_context(
+    context: &Context,
+    prefix: &str,
+    files: &[(&str, &str)],
+) -> (TempDir, Chip) {
+    let dir = TempDir::new();
+    dir.file("name", prefix);
+    for (name, contents) in files {
+        dir.file(name, contents);
+    }
+    let chip = Chip::from_path(dir.path(), None, context).unwrap();
+    (dir, chip)
+}
@@ -0,0 +1,43 @@
+// SPDX-FileCopyrightText: 2026 Camille019
+// SPDX-License-Identifier: MPL-2.0
+
+/// Unit of a subfeature value, once scaled by the library.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Unit {
+    Celsius,
+    Volt,
+    Ampere,
+    Watt,
+    Joule,
+    Rpm,
+    Percent,
+    Hertz,
+    Second,
+    /// Flags, modes, divisors and other unitless values.
+    Dimensionless,
+}
+
+impl Unit {
+    /// Symbol of the unit, as printed after a value. Empty for `Dimensionless`.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Unit::Celsius => "°C",
+            Unit::Volt => "V",
+            Unit::Ampere => "A",
+            Unit::Watt => "W",
+            Unit::Joule => "J",
+            Unit::Rpm => "RPM",
+            Unit::Percent => "%",
+            Unit::Hertz => "Hz",
+            Unit::Second => "s",
+            Unit::Dimensionless => "",
+        }
+    }
+}
+
+/// A subfeature value along with its unit.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Measurement {
+    pub value: f64,
+    pub unit: Unit,
+}
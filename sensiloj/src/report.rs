@@ -0,0 +1,1038 @@
+// SPDX-FileCopyrightText: 2021 Camille019
+// SPDX-License-Identifier: GPL-2.0-only
+
+//! Text report of a chip, in the style of the lm-sensors `sensors` program.
+
+use std::fmt;
+use std::sync::LazyLock;
+
+use hwmon::subfeature::*;
+use hwmon::{Chip, Feature, FeatureType, Unit};
+
+static HYST_STR: &str = "hyst";
+
+#[derive(Debug)]
+struct SubfeatureData {
+    /// Subfeature value. Not used for alarms.
+    value: f64,
+    /// Subfeature name
+    name: String,
+    /// Unit of the subfeature value.
+    value_unit: Unit,
+    /// Unit to be displayed for this subfeature.
+    /// This field is optional.
+    unit: String,
+}
+
+#[derive(Debug)]
+struct SubfeatureList {
+    sf_type: SubfeatureType,
+    /// Complementary subfeatures to be displayed if subfeature exists
+    comp: Vec<SubfeatureList>,
+    /// Subfeature name to be printed
+    name: String,
+}
+
+/// Scale a value to keep it in the `[1, 1000[` range, and return the scaled value
+/// with its SI prefix symbol.
+fn scale_value(value: f64) -> (f64, &'static str) {
+    static PREFIX_SCALE: LazyLock<Vec<(f64, &'static str)>> = LazyLock::new(|| {
+        vec![
+            (1e-6, "n"),
+            (1e-3, "u"),
+            (1.0, "m"),
+            (1e3, ""),
+            (1e6, "k"),
+            (1e9, "M"),
+            (0.0, "G"),
+        ]
+    });
+
+    let abs_value = value.abs();
+    let mut divisor = 1e-9;
+
+    if abs_value == 0.0 {
+        return (value, "");
+    }
+
+    let mut idx = 0;
+    for (upper_bound, _) in PREFIX_SCALE.iter() {
+        if *upper_bound == 0.0 || abs_value <= *upper_bound {
+            break;
+        }
+        divisor = *upper_bound;
+        idx += 1;
+    }
+
+    (value / divisor, PREFIX_SCALE[idx].1)
+}
+
+fn get_label_length(chip: &Chip) -> usize {
+    let mut max_len = 11;
+    for feature in chip.features_iter() {
+        let len = feature.label().len();
+        if len > max_len {
+            max_len = len;
+        }
+    }
+
+    // One more for the colon, and one more to guarantee at least one
+    // space between that colon and the value */
+    max_len + 2
+}
+
+fn print_label(f: &mut fmt::Formatter, label: &str, length: usize) -> fmt::Result {
+    write!(f, "{}{:len$}", label, ":", len = (length - label.len()))
+}
+
+fn print_alarms(
+    f: &mut fmt::Formatter,
+    alarms: &[SubfeatureData],
+    leading_spaces: usize,
+) -> fmt::Result {
+    write!(f, "{:>len$}", "ALARM", len = (leading_spaces + 7))?;
+    if !alarms.is_empty() {
+        let mut printed = false;
+        for alarm in alarms {
+            if !alarm.name.is_empty() {
+                if !printed {
+                    write!(f, " (")?;
+                } else {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{}", alarm.name)?;
+                printed = true;
+            }
+        }
+        if printed {
+            write!(f, ")")?;
+        }
+    }
+    Ok(())
+}
+
+/// Print limits on two columns, filling lines first, except for hysteresis
+/// which must always go on the right column, with the limit it relates to
+/// being in the left column on the same line.
+fn print_limits(
+    f: &mut fmt::Formatter,
+    limits: &[SubfeatureData],
+    alarms: &[SubfeatureData],
+    label_length: usize,
+    print_limit: fn(&mut fmt::Formatter, &SubfeatureData) -> fmt::Result,
+) -> fmt::Result {
+    let mut alarms_printed = false;
+
+    let mut slot = 0;
+    for (i, limit) in limits.iter().enumerate() {
+        if (slot & 1) != 1 {
+            if slot != 0 {
+                write!(f, "\n{:>len$}", "", len = (label_length + 10))?;
+            }
+            write!(f, "(")?;
+        } else {
+            write!(f, ", ")?;
+        }
+        print_limit(f, limit)?;
+
+        let skip = if limits
+            .get(i + 2)
+            .map(|s| s.name == HYST_STR)
+            .map(|b| b && (slot & 1) != 1)
+            .unwrap_or(false)
+        {
+            1
+        } else {
+            0
+        };
+
+        if (((slot + skip) & 1) == 1) || (i == (limits.len() - 1)) {
+            write!(f, ")")?;
+            if !alarms.is_empty() && !alarms_printed {
+                print_alarms(f, alarms, if (slot & 1) == 1 { 0 } else { 16 })?;
+                alarms_printed = true;
+            }
+        }
+        slot += skip + 1;
+    }
+    if !alarms.is_empty() && !alarms_printed {
+        print_alarms(f, alarms, 32)?;
+    }
+    Ok(())
+}
+
+fn get_sensor_limit_data(
+    feature: &Feature,
+    sfl_vec: &[SubfeatureList],
+    limits: &mut Vec<SubfeatureData>,
+    alarms: &mut Vec<SubfeatureData>,
+) {
+    for sfl in sfl_vec.iter() {
+        if let Some(value) = feature
+            .subfeature(sfl.sf_type)
+            .and_then(|sf| sf.read_value().ok())
+        {
+            if sfl.sf_type.is_alarm() {
+                // Only queue alarm subfeatures if the alarm
+                // is active, and don't store the alarm value
+                // (it is implied to be active if queued).
+                if value != 0.0 {
+                    let alarm = SubfeatureData {
+                        value,
+                        name: sfl.name.clone(),
+                        value_unit: sfl.sf_type.unit(),
+                        unit: Default::default(),
+                    };
+                    alarms.push(alarm);
+                }
+            } else {
+                // Always queue limit subfeatures with their value.
+                let limit = SubfeatureData {
+                    value,
+                    name: sfl.name.clone(),
+                    value_unit: sfl.sf_type.unit(),
+                    unit: Default::default(),
+                };
+                limits.push(limit);
+            }
+            get_sensor_limit_data(feature, &sfl.comp, limits, alarms);
+        }
+    }
+}
+
+fn print_feature_fan(
+    f: &mut fmt::Formatter,
+    feature: &Feature,
+    label_length: usize,
+) -> fmt::Result {
+    let label = feature.label();
+    print_label(f, label.as_ref(), label_length)?;
+
+    let fault = feature
+        .subfeature(SubfeatureType::Fan(Fan::Fault))
+        .and_then(|sf| sf.read_value().map(|val| val != 0.0).ok())
+        .unwrap_or(false);
+    if fault {
+        write!(f, "   FAULT")?;
+    } else if let Some(input) = feature
+        .subfeature(SubfeatureType::Fan(Fan::Input))
+        .and_then(|sf| sf.read_value().ok())
+    {
+        write!(f, "{:4.0} RPM", input)?;
+    } else {
+        write!(f, "     N/A")?;
+    }
+
+    // Print limits
+    let sfmin = feature
+        .subfeature(SubfeatureType::Fan(Fan::Min))
+        .and_then(|sf| sf.read_value().ok());
+    let sfmax = feature
+        .subfeature(SubfeatureType::Fan(Fan::Max))
+        .and_then(|sf| sf.read_value().ok());
+    let sftarget = feature
+        .subfeature(SubfeatureType::Fan(Fan::Target))
+        .and_then(|sf| sf.read_value().ok());
+    let sfdiv = feature
+        .subfeature(SubfeatureType::Fan(Fan::Div))
+        .and_then(|sf| sf.read_value().ok());
+
+    if sfmin.is_some() || sfmax.is_some() || sftarget.is_some() || sfdiv.is_some() {
+        write!(f, "  (")?;
+        if let Some(value) = sfmin {
+            write!(f, "min = {:4.0} RPM", value)?;
+        }
+        if let Some(value) = sfmax {
+            if sfmin.is_some() {
+                write!(f, ", ")?;
+            }
+            write!(f, "max = {:4.0} RPM", value)?;
+        }
+        if let Some(value) = sftarget {
+            if sfmin.is_some() || sfmax.is_some() {
+                write!(f, ", ")?;
+            }
+            write!(f, "target = {:4.0} RPM", value)?;
+        }
+        if let Some(value) = sfdiv {
+            if sfmin.is_some() || sfmax.is_some() || sftarget.is_some() {
+                write!(f, ", ")?;
+            }
+            write!(f, "div = {:1.0}", value)?;
+        }
+        write!(f, ")")?;
+    }
+
+    let sf_alarm = feature
+        .subfeature(SubfeatureType::Fan(Fan::Alarm))
+        .and_then(|sf| sf.is_alarm_active().ok())
+        .unwrap_or(false);
+    let sfmin_alarm = feature
+        .subfeature(SubfeatureType::Fan(Fan::Min_Alarm))
+        .and_then(|sf| sf.is_alarm_active().ok())
+        .unwrap_or(false);
+    let sfmax_alarm = feature
+        .subfeature(SubfeatureType::Fan(Fan::Max_Alarm))
+        .and_then(|sf| sf.is_alarm_active().ok())
+        .unwrap_or(false);
+    if sf_alarm || sfmin_alarm || sfmax_alarm {
+        write!(f, "  ALARM")?;
+    }
+
+    writeln!(f)
+}
+
+fn print_feature_pwm(
+    f: &mut fmt::Formatter,
+    feature: &Feature,
+    label_length: usize,
+) -> fmt::Result {
+    let label = feature.label();
+    print_label(f, label.as_ref(), label_length)?;
+
+    if let Some(value) = feature
+        .subfeature(SubfeatureType::Pwm(Pwm::Pwm))
+        .and_then(|sf| sf.read_value().ok())
+    {
+        write!(f, "{:6.1}%", pwm_duty_cycle(value))?;
+    } else {
+        write!(f, "    N/A")?;
+    }
+
+    let sfenable = feature
+        .subfeature(SubfeatureType::Pwm(Pwm::Enable))
+        .and_then(|sf| sf.read_pwm_enable().ok());
+    let sfmode = feature
+        .subfeature(SubfeatureType::Pwm(Pwm::Mode))
+        .and_then(|sf| sf.read_value().ok());
+    let sffreq = feature
+        .subfeature(SubfeatureType::Pwm(Pwm::Freq))
+        .and_then(|sf| sf.read_value().ok());
+
+    if sfenable.is_some() || sfmode.is_some() || sffreq.is_some() {
+        write!(f, "  (")?;
+        if let Some(enable) = sfenable {
+            match enable {
+                PwmEnable::NoControl => write!(f, "enable = full")?,
+                PwmEnable::Manual => write!(f, "enable = manual")?,
+                PwmEnable::Automatic(2) => write!(f, "enable = auto")?,
+                PwmEnable::Automatic(mode) => write!(f, "enable = auto {}", mode)?,
+            }
+        }
+        if let Some(value) = sfmode {
+            if sfenable.is_some() {
+                write!(f, ", ")?;
+            }
+            if value == 0.0 {
+                write!(f, "mode = DC")?;
+            } else {
+                write!(f, "mode = PWM")?;
+            }
+        }
+        if let Some(value) = sffreq {
+            if sfenable.is_some() || sfmode.is_some() {
+                write!(f, ", ")?;
+            }
+            write!(f, "freq = {:4.1} Hz", value)?;
+        }
+        write!(f, ")")?;
+    }
+
+    let points = feature.auto_points();
+    if !points.is_empty() {
+        write!(f, "\n{:>len$}(auto points: ", "", len = (label_length + 10))?;
+        for (i, (temp, pwm)) in points.iter().enumerate() {
+            if i != 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{:+.1}°C = {:.1}%", temp, pwm_duty_cycle(*pwm))?;
+        }
+        write!(f, ")")?;
+    }
+
+    writeln!(f)
+}
+
+/// Convert a raw pwm value, from 0 to 255, to a duty cycle percentage.
+fn pwm_duty_cycle(value: f64) -> f64 {
+    value / 255.0 * 100.0
+}
+
+macro_rules! make_sflist_item {
+    (feature: $Feature:ident, properties: { $SfType:ident } ) => {
+        SubfeatureList {
+            sf_type: SubfeatureType::$Feature($Feature::$SfType),
+            name: String::new(),
+            comp: Vec::new(),
+        }
+    };
+    (feature: $Feature:ident, properties: { $SfType:ident, $name:expr } ) => {
+        SubfeatureList {
+            sf_type: SubfeatureType::$Feature($Feature::$SfType),
+            name: String::from($name),
+            comp: Vec::new(),
+        }
+    };
+    (feature: $Feature:ident, properties: { $SfType:ident, $name:expr, $comp:tt }) => {
+        SubfeatureList {
+            sf_type: SubfeatureType::$Feature($Feature::$SfType),
+            name: String::from($name),
+            comp: make_sflist! {
+                feature: $Feature,
+                list = $comp
+            },
+        }
+    };
+}
+
+macro_rules! make_sflist {
+    (feature: $Feature:ident, list = [ $($properties:tt),* $(,)* ] ) => {
+        vec![
+            $(make_sflist_item!{
+                feature: $Feature,
+                properties: $properties
+            },)*
+        ]
+    };
+}
+
+static TEMP_SENSORS: LazyLock<Vec<SubfeatureList>> = LazyLock::new(|| {
+    make_sflist! {
+        feature: Temperature,
+        list = [
+            { Alarm },
+            { Crit_Min_Alarm, "LCRIT" },
+            { Min_Alarm, "LOW" },
+            { Max_Alarm, "HIGH" },
+            { Crit_Max_Alarm, "CRIT" },
+            { Emergency_Alarm, "EMERGENCY" },
+            { Min, "low", [ {Min_Hyst, HYST_STR} ] },
+            { Max, "high", [ {Max_Hyst, HYST_STR} ] },
+            { Crit_Min, "crit low", [ {Crit_Min_Hyst, HYST_STR} ] },
+            { Crit_Max, "crit", [ {Crit_Max_Hyst, HYST_STR} ] },
+            { Emergency, "emerg" , [ {Emergency_Hyst, HYST_STR} ] },
+            { Lowest, "lowest" },
+            { Highest, "highest" },
+        ]
+    }
+});
+
+fn print_feature_temp(
+    f: &mut fmt::Formatter,
+    feature: &Feature,
+    label_length: usize,
+) -> fmt::Result {
+    let label = feature.label();
+    print_label(f, label.as_ref(), label_length)?;
+
+    let fault = feature
+        .subfeature(SubfeatureType::Temperature(Temperature::Fault))
+        .and_then(|sf| sf.read_value().map(|val| val != 0.0).ok())
+        .unwrap_or(false);
+    if fault {
+        write!(f, "   FAULT  ")?;
+    } else if let Some(input) = feature
+        .subfeature(SubfeatureType::Temperature(Temperature::Input))
+        .and_then(|sf| sf.read_value().ok())
+    {
+        write!(f, "{:+6.1}°C  ", input)?;
+    } else {
+        write!(f, "     N/A  ")?;
+    }
+
+    // Print limits
+    let mut alarms = Vec::new();
+    let mut sensors = Vec::new();
+
+    get_sensor_limit_data(feature, &TEMP_SENSORS, &mut sensors, &mut alarms);
+
+    print_limits(f, &sensors, &alarms, label_length, |f, limit| {
+        write!(
+            f,
+            "{:-4} = {:+5.1}°C{}",
+            limit.name, limit.value, limit.unit
+        )
+    })?;
+
+    // print out temperature sensor info
+    if let Ok(sens) = feature.read_temp_type() {
+        let buff = match sens {
+            TempSensorKind::Disabled => "disabled",
+            TempSensorKind::CpuDiode => "CPU diode",
+            TempSensorKind::Transistor => "transistor",
+            TempSensorKind::ThermalDiode => "thermal diode",
+            TempSensorKind::Thermistor => "thermistor",
+            TempSensorKind::AmdAmdsi => "AMD AMDSI",
+            TempSensorKind::IntelPeci => "Intel PECI",
+            TempSensorKind::Unknown => "unknown",
+        };
+
+        write!(f, "  sensor = {}", buff)?;
+    }
+
+    writeln!(f)
+}
+
+static VOLTAGE_SENSORS: LazyLock<Vec<SubfeatureList>> = LazyLock::new(|| {
+    make_sflist! {
+        feature: Voltage,
+        list = [
+            { Alarm },
+            { Crit_Min_Alarm, "LCRIT" },
+            { Min_Alarm, "MIN" },
+            { Max_Alarm, "MAX" },
+            { Crit_Max_Alarm, "CRIT" },
+            { Crit_Min, "crit min" },
+            { Min, "min" },
+            { Max, "max" },
+            { Crit_Max, "crit max" },
+            { Average, "avg" },
+            { Lowest, "lowest" },
+            { Highest, "highest" },
+        ]
+    }
+});
+
+fn print_feature_volt(
+    f: &mut fmt::Formatter,
+    feature: &Feature,
+    label_length: usize,
+) -> fmt::Result {
+    let label = feature.label();
+    print_label(f, label.as_ref(), label_length)?;
+
+    if let Some(input) = feature
+        .subfeature(SubfeatureType::Voltage(Voltage::Input))
+        .and_then(|sf| sf.read_value().ok())
+    {
+        write!(f, "{:+6.2} V  ", input)?;
+    } else {
+        write!(f, "     N/A  ")?;
+    }
+
+    // Print limits
+    let mut alarms = Vec::new();
+    let mut sensors = Vec::new();
+
+    get_sensor_limit_data(feature, &VOLTAGE_SENSORS, &mut sensors, &mut alarms);
+
+    print_limits(f, &sensors, &alarms, label_length, |f, limit| {
+        write!(f, "{} = {:+6.2} V{}", limit.name, limit.value, limit.unit)
+    })?;
+
+    writeln!(f)
+}
+
+static CURRENT_SENSORS: LazyLock<Vec<SubfeatureList>> = LazyLock::new(|| {
+    make_sflist! {
+        feature: Current,
+        list = [
+            { Alarm },
+            { Crit_Min_Alarm, "LCRIT" },
+            { Min_Alarm, "MIN" },
+            { Max_Alarm, "MAX" },
+            { Crit_Max_Alarm, "CRIT" },
+            { Crit_Min, "crit min" },
+            { Min, "min" },
+            { Max, "max" },
+            { Crit_Max, "crit max" },
+            { Average, "avg" },
+            { Lowest, "lowest" },
+            { Highest, "highest" },
+        ]
+    }
+});
+
+fn print_feature_curr(
+    f: &mut fmt::Formatter,
+    feature: &Feature,
+    label_length: usize,
+) -> fmt::Result {
+    let label = feature.label();
+    print_label(f, label.as_ref(), label_length)?;
+
+    if let Some(input) = feature
+        .subfeature(SubfeatureType::Current(Current::Input))
+        .and_then(|sf| sf.read_value().ok())
+    {
+        write!(f, "{:+6.2} A  ", input)?;
+    } else {
+        write!(f, "     N/A  ")?;
+    }
+
+    // Print limits
+    let mut alarms = Vec::new();
+    let mut sensors = Vec::new();
+
+    get_sensor_limit_data(feature, &CURRENT_SENSORS, &mut sensors, &mut alarms);
+
+    print_limits(f, &sensors, &alarms, label_length, |f, limit| {
+        write!(f, "{} = {:+6.2} A{}", limit.name, limit.value, limit.unit)
+    })?;
+
+    writeln!(f)
+}
+
+static POWER_COMMON_SENSORS: LazyLock<Vec<SubfeatureList>> = LazyLock::new(|| {
+    make_sflist! {
+        feature: Power,
+        list = [
+            { Alarm },
+            { Min_Alarm, "MIN" },
+            { Max_Alarm, "MAX" },
+            { Crit_Min_Alarm, "LCRIT" },
+            { Crit_Max_Alarm, "CRIT" },
+            { Cap_Alarm, "CAP" },
+            { Max, "max" },
+            { Min, "min" },
+            { Crit_Min, "lcrit" },
+            { Crit_Max, "crit" },
+            { Cap, "cap" },
+        ]
+    }
+});
+
+static POWER_INST_SENSORS: LazyLock<Vec<SubfeatureList>> = LazyLock::new(|| {
+    make_sflist! {
+        feature: Power,
+        list = [
+            { Input_Lowest, "lowest" },
+            { Input_Highest, "highest" },
+            { Average, "avg" },
+            { Average_Lowest, "avg lowest" },
+            { Average_Highest, "avg highest" },
+            { Average_Interval, "interval" },
+        ]
+    }
+});
+
+static POWER_AVG_SENSORS: LazyLock<Vec<SubfeatureList>> = LazyLock::new(|| {
+    make_sflist! {
+        feature: Power,
+        list = [
+            { Average_Lowest, "lowest" },
+            { Average_Highest, "highest" },
+            { Average_Interval, "interval" },
+        ]
+    }
+});
+
+fn print_feature_power(
+    f: &mut fmt::Formatter,
+    feature: &Feature,
+    label_length: usize,
+) -> fmt::Result {
+    let label = feature.label();
+    print_label(f, label.as_ref(), label_length)?;
+
+    let mut alarms = Vec::new();
+    let mut sensors = Vec::new();
+
+    // Power sensors come in 2 flavors: instantaneous and averaged.
+    // Most devices only support one flavor, so we try to display the
+    // average power if the instantaneous power attribute does not exist.
+    // If both instantaneous power and average power are supported,
+    // average power is displayed as limit.
+    let mut sf = feature
+        .subfeature(SubfeatureType::Power(Power::Input))
+        .and_then(|sf| sf.read_value().ok());
+
+    if sf.is_some() {
+        get_sensor_limit_data(feature, &POWER_INST_SENSORS, &mut sensors, &mut alarms);
+    } else {
+        get_sensor_limit_data(feature, &POWER_AVG_SENSORS, &mut sensors, &mut alarms);
+    }
+    // Add sensors common to both flavors.
+    get_sensor_limit_data(feature, &POWER_COMMON_SENSORS, &mut sensors, &mut alarms);
+
+    if sf.is_none() {
+        sf = feature
+            .subfeature(SubfeatureType::Power(Power::Average))
+            .and_then(|sf| sf.read_value().ok());
+    }
+
+    if let Some(value) = sf {
+        let (value, unit) = scale_value(value);
+        write!(
+            f,
+            "{:6.2} {}{:len$}",
+            value,
+            unit,
+            "W",
+            len = (3 - unit.len())
+        )?;
+    } else {
+        write!(f, "     N/A  ")?;
+    }
+
+    for sens in sensors.iter_mut() {
+        // Powers are scaled, other units like the averaging interval are
+        // displayed as is.
+        if sens.value_unit == Unit::Watt {
+            let (value, unit) = scale_value(sens.value);
+            sens.value = value;
+            sens.unit = format!("{}W", unit);
+        } else {
+            sens.unit = sens.value_unit.symbol().to_string();
+        }
+    }
+
+    // Print limits
+    print_limits(f, &sensors, &alarms, label_length, |f, limit| {
+        write!(f, "{} = {:6.2} {}", limit.name, limit.value, limit.unit)
+    })?;
+
+    writeln!(f)
+}
+
+fn print_feature_energy(
+    f: &mut fmt::Formatter,
+    feature: &Feature,
+    label_length: usize,
+) -> fmt::Result {
+    let label = feature.label();
+    print_label(f, label.as_ref(), label_length)?;
+
+    if let Some(val) = feature
+        .subfeature(SubfeatureType::Energy(Energy::Input))
+        .and_then(|sf| sf.read_value().ok())
+    {
+        let (val, unit) = scale_value(val);
+        writeln!(f, "{:6.2} {}J", val, unit)
+    } else {
+        writeln!(f, "     N/A")
+    }
+}
+
+static HUMIDITY_SENSORS: LazyLock<Vec<SubfeatureList>> = LazyLock::new(|| {
+    make_sflist! {
+        feature: Humidity,
+        list = [
+            { Min_Alarm, "MIN" },
+            { Max_Alarm, "MAX" },
+            { Min, "min" },
+            { Max, "max" },
+        ]
+    }
+});
+
+fn print_feature_humidity(
+    f: &mut fmt::Formatter,
+    feature: &Feature,
+    label_length: usize,
+) -> fmt::Result {
+    let label = feature.label();
+    print_label(f, label.as_ref(), label_length)?;
+
+    if feature.fault().unwrap_or(false) {
+        write!(f, "   FAULT  ")?;
+    } else if let Some(input) = feature
+        .subfeature(SubfeatureType::Humidity(Humidity::Input))
+        .and_then(|sf| sf.read_value().ok())
+    {
+        write!(f, "{:5.1} %RH ", input)?;
+    } else {
+        write!(f, "     N/A  ")?;
+    }
+
+    // Print limits
+    let mut alarms = Vec::new();
+    let mut sensors = Vec::new();
+
+    get_sensor_limit_data(feature, &HUMIDITY_SENSORS, &mut sensors, &mut alarms);
+
+    print_limits(f, &sensors, &alarms, label_length, |f, limit| {
+        write!(f, "{} = {:5.1} %RH{}", limit.name, limit.value, limit.unit)
+    })?;
+
+    writeln!(f)
+}
+
+fn print_feature_cpu(
+    f: &mut fmt::Formatter,
+    feature: &Feature,
+    label_length: usize,
+) -> fmt::Result {
+    if let Some(sf) = feature.subfeature(SubfeatureType::Cpu) {
+        let label = feature.label();
+        if let Ok(val) = sf.read_value() {
+            print_label(f, label.as_ref(), label_length)?;
+            writeln!(f, "{:+6.3} V", val)?;
+        }
+    }
+    Ok(())
+}
+
+fn print_feature_intrusion(
+    f: &mut fmt::Formatter,
+    feature: &Feature,
+    label_length: usize,
+) -> fmt::Result {
+    if let Some(sf) = feature.subfeature(SubfeatureType::Intrusion(Intrusion::Alarm)) {
+        let label = feature.label();
+        if let Ok(alarm) = sf.is_alarm_active() {
+            print_label(f, label.as_ref(), label_length)?;
+            if alarm {
+                writeln!(f, "ALARM")?;
+            } else {
+                writeln!(f, "OK")?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_feature_beep_enable(
+    f: &mut fmt::Formatter,
+    feature: &Feature,
+    label_length: usize,
+) -> fmt::Result {
+    if let Some(sf) = feature.subfeature(SubfeatureType::BeepEnable) {
+        let label = feature.label();
+        if let Ok(val) = sf.read_value() {
+            print_label(f, label.as_ref(), label_length)?;
+            if val == 0.0 {
+                writeln!(f, "disabled")?;
+            } else {
+                writeln!(f, "enabled")?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Text report of a chip: its name, its adapter and one line per feature, like the
+/// lm-sensors `sensors` program.
+pub struct Report<'a>(pub &'a Chip);
+
+impl fmt::Display for Report<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let chip = self.0;
+        writeln!(f, "{}", chip.name())?;
+        if let Some(name) = chip.bus().adapter_name() {
+            writeln!(f, "Adapter: {}", name)?;
+        }
+
+        let label_length = get_label_length(chip);
+
+        for feature in chip.features_iter() {
+            match feature.get_type() {
+                FeatureType::Fan => print_feature_fan(f, feature, label_length)?,
+                FeatureType::Pwm => print_feature_pwm(f, feature, label_length)?,
+                FeatureType::Temperature => print_feature_temp(f, feature, label_length)?,
+                FeatureType::Voltage => print_feature_volt(f, feature, label_length)?,
+                FeatureType::Current => print_feature_curr(f, feature, label_length)?,
+                FeatureType::Power => print_feature_power(f, feature, label_length)?,
+                FeatureType::Energy => print_feature_energy(f, feature, label_length)?,
+                FeatureType::Humidity => print_feature_humidity(f, feature, label_length)?,
+                FeatureType::Cpu => print_feature_cpu(f, feature, label_length)?,
+                FeatureType::Intrusion => print_feature_intrusion(f, feature, label_length)?,
+                FeatureType::BeepEnable => print_feature_beep_enable(f, feature, label_length)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hwmon::{Context, MockFs};
+
+    /// Build a virtual chip named `prefix` from a list of `(attribute, contents)` files.
+    fn mock_chip(prefix: &str, files: &[(&str, &str)]) -> Chip {
+        let mut mock = MockFs::new();
+        mock.dir("/sys/class/i2c-adapter");
+        mock.file("/sys/class/hwmon/hwmon0/name", prefix);
+        for (name, contents) in files {
+            mock.file(format!("/sys/class/hwmon/hwmon0/{}", name), contents);
+        }
+        let context = Context::with_mock(mock).unwrap();
+        hwmon::read_sysfs_chips(&context).unwrap().remove(0)
+    }
+
+    #[test]
+    fn scale_value_prefixes() {
+        assert_eq!(scale_value(0.0), (0.0, ""));
+        assert_eq!(scale_value(0.0125), (12.5, "m"));
+        assert_eq!(scale_value(250.0), (250.0, ""));
+        assert_eq!(scale_value(-2500.0), (-2.5, "k"));
+    }
+
+    #[test]
+    fn chip_display_report() {
+        let chip = mock_chip(
+            "dummy",
+            &[
+                ("temp1_input", "45000"),
+                ("temp1_max", "80000"),
+                ("temp1_max_hyst", "75000"),
+                ("temp1_crit", "100000"),
+                ("temp1_label", "Core 0"),
+                ("in0_input", "1200"),
+                ("in0_min", "1000"),
+                ("in0_max", "1400"),
+                ("in0_max_alarm", "1"),
+                ("fan1_input", "1250"),
+                ("fan1_min", "600"),
+            ],
+        );
+
+        let expected = "\
+dummy-virtual-0
+Adapter: Virtual device
+fan1:        1250 RPM  (min =  600 RPM)
+Core 0:       +45.0°C  (high = +80.0°C, hyst = +75.0°C)
+                       (crit = +100.0°C)
+in0:          +1.20 V  (min =  +1.00 V, max =  +1.40 V)  ALARM (MAX)
+";
+        assert_eq!(Report(&chip).to_string(), expected);
+    }
+
+    #[test]
+    fn chip_display_power_interval() {
+        let chip = mock_chip(
+            "dummy",
+            &[
+                ("power1_input", "12500000"),
+                ("power1_average_interval", "1500"),
+                ("power1_cap", "65000000"),
+            ],
+        );
+        let report = Report(&chip).to_string();
+        assert!(report.contains("interval =   1.50 s"), "{}", report);
+        assert!(report.contains("cap =  65.00 W"), "{}", report);
+    }
+
+    #[test]
+    fn chip_display_humidity_limits() {
+        let chip = mock_chip(
+            "dummy",
+            &[
+                ("humidity1_input", "45500"),
+                ("humidity1_min", "20000"),
+                ("humidity1_max", "80000"),
+                ("humidity1_min_alarm", "1"),
+            ],
+        );
+
+        let expected = "\
+dummy-virtual-0
+Adapter: Virtual device
+humidity1:    45.5 %RH (min =  20.0 %RH, max =  80.0 %RH)  ALARM (MIN)
+";
+        assert_eq!(Report(&chip).to_string(), expected);
+    }
+
+    #[test]
+    fn chip_display_energy_unreadable() {
+        let mut mock = MockFs::new();
+        mock.dir("/sys/class/i2c-adapter");
+        mock.file("/sys/class/hwmon/hwmon0/name", "dummy");
+        mock.file("/sys/class/hwmon/hwmon0/energy1_input", "1500000");
+        mock.file_with_mode("/sys/class/hwmon/hwmon0/energy2_input", "", 0o200);
+        let context = Context::with_mock(mock).unwrap();
+        let chip = hwmon::read_sysfs_chips(&context).unwrap().remove(0);
+
+        let expected = "\
+dummy-virtual-0
+Adapter: Virtual device
+energy1:       1.50 J
+energy2:          N/A
+";
+        assert_eq!(Report(&chip).to_string(), expected);
+    }
+
+    #[test]
+    fn chip_display_fan_target() {
+        let chip = mock_chip(
+            "dummy",
+            &[
+                ("fan1_input", "1187"),
+                ("fan1_min", "600"),
+                ("fan1_target", "1200"),
+            ],
+        );
+
+        let expected = "\
+dummy-virtual-0
+Adapter: Virtual device
+fan1:        1187 RPM  (min =  600 RPM, target = 1200 RPM)
+";
+        assert_eq!(Report(&chip).to_string(), expected);
+    }
+
+    #[test]
+    fn chip_display_fan_limits() {
+        let chip = mock_chip(
+            "dummy",
+            &[
+                ("fan1_input", "1187"),
+                ("fan1_min", "600"),
+                ("fan1_max", "3000"),
+                ("fan1_div", "4"),
+            ],
+        );
+
+        let expected = "\
+dummy-virtual-0
+Adapter: Virtual device
+fan1:        1187 RPM  (min =  600 RPM, max = 3000 RPM, div = 4)
+";
+        assert_eq!(Report(&chip).to_string(), expected);
+    }
+
+    #[test]
+    fn chip_display_pwm_manual() {
+        let chip = mock_chip(
+            "dummy",
+            &[("pwm1", "128"), ("pwm1_enable", "1"), ("pwm1_mode", "1")],
+        );
+
+        let expected = "\
+dummy-virtual-0
+Adapter: Virtual device
+pwm1:          50.2%  (enable = manual, mode = PWM)
+";
+        assert_eq!(Report(&chip).to_string(), expected);
+    }
+
+    #[test]
+    fn chip_display_pwm_mode_freq() {
+        let chip = mock_chip(
+            "dummy",
+            &[("pwm1", "0"), ("pwm1_mode", "0"), ("pwm1_freq", "25000")],
+        );
+
+        let expected = "\
+dummy-virtual-0
+Adapter: Virtual device
+pwm1:           0.0%  (mode = DC, freq = 25000.0 Hz)
+";
+        assert_eq!(Report(&chip).to_string(), expected);
+    }
+
+    #[test]
+    fn chip_display_pwm_auto_points() {
+        let chip = mock_chip(
+            "dummy",
+            &[
+                ("pwm1", "255"),
+                ("pwm1_enable", "2"),
+                ("pwm1_auto_point1_temp", "40000"),
+                ("pwm1_auto_point1_pwm", "51"),
+                ("pwm1_auto_point2_temp", "70000"),
+                ("pwm1_auto_point2_pwm", "255"),
+            ],
+        );
+
+        let expected = "\
+dummy-virtual-0
+Adapter: Virtual device
+pwm1:         100.0%  (enable = auto)
+                       (auto points: +40.0°C = 20.0%, +70.0°C = 100.0%)
+";
+        assert_eq!(Report(&chip).to_string(), expected);
+    }
+}